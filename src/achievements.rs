@@ -1,11 +1,12 @@
 use crate::buildings::BuildingType;
 use crate::buildings::Buildings;
 use crate::research::{Research, Tech};
+use crate::statistics::Statistics;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
 /// Tracks unlocked achievements
-#[derive(Default, Serialize, Deserialize)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct Achievements {
     unlocked: HashSet<String>,
 }
@@ -13,7 +14,12 @@ pub struct Achievements {
 impl Achievements {
     /// Check for new achievements based on game state. Returns messages for
     /// any newly unlocked achievements.
-    pub fn check(&mut self, buildings: &Buildings, research: &Research) -> Vec<String> {
+    pub fn check(
+        &mut self,
+        buildings: &Buildings,
+        research: &Research,
+        stats: &Statistics,
+    ) -> Vec<String> {
         let mut msgs = Vec::new();
         if buildings.level(BuildingType::Farm) >= 1
             && self.unlocked.insert("First Farm".to_string())
@@ -27,6 +33,9 @@ impl Achievements {
         {
             msgs.push("Achievement unlocked: Discovered Mining".to_string());
         }
+        if stats.buildings_sold >= 1 && self.unlocked.insert("Demolition".to_string()) {
+            msgs.push("Achievement unlocked: Demolition".to_string());
+        }
         msgs
     }
 
@@ -0,0 +1,93 @@
+use crate::buildings::BuildingType;
+use crate::research::Tech;
+use crate::upgrades::UpgradeType;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// Prestige points that unlock automation without research
+pub const AUTOMATION_PRESTIGE: u32 = 1;
+
+/// A purchase waiting in the queue
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind")]
+pub enum QueuedAction {
+    /// Build `count` more levels of a building
+    Build {
+        /// Building to construct
+        building: BuildingType,
+        /// Levels still to build
+        count: u32,
+    },
+    /// Research a technology
+    Research {
+        /// Technology to unlock
+        tech: Tech,
+    },
+    /// Buy one level of an upgrade
+    Upgrade {
+        /// Upgrade to purchase
+        upgrade: UpgradeType,
+    },
+}
+
+/// Auto-builders and the purchase queue
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct Automation {
+    /// Fraction of each resource an auto-builder may spend on one level
+    auto_build: HashMap<BuildingType, f64>,
+    /// Purchases made strictly in order
+    queue: VecDeque<QueuedAction>,
+}
+
+impl Automation {
+    /// Fraction of stock the auto-builder for a building may spend, 0 when off
+    pub fn auto_build(&self, ty: BuildingType) -> f64 {
+        self.auto_build.get(&ty).copied().unwrap_or(0.0)
+    }
+
+    /// Set the spend limit of an auto-builder. A fraction of 0 turns it off.
+    pub fn set_auto_build(&mut self, ty: BuildingType, fraction: f64) {
+        let fraction = fraction.clamp(0.0, 1.0);
+        if fraction > 0.0 {
+            self.auto_build.insert(ty, fraction);
+        } else {
+            self.auto_build.remove(&ty);
+        }
+    }
+
+    /// Add a purchase to the back of the queue
+    pub fn enqueue(&mut self, action: QueuedAction) {
+        self.queue.push_back(action);
+    }
+
+    /// Next purchase to attempt
+    pub fn front(&self) -> Option<QueuedAction> {
+        self.queue.front().copied()
+    }
+
+    /// Mark one step of the front purchase as done
+    pub fn complete_front(&mut self) {
+        match self.queue.front_mut() {
+            Some(QueuedAction::Build { count, .. }) if *count > 1 => *count -= 1,
+            Some(_) => {
+                self.queue.pop_front();
+            }
+            None => {}
+        }
+    }
+
+    /// Remove the front purchase along with any levels it has left
+    pub fn drop_front(&mut self) {
+        self.queue.pop_front();
+    }
+
+    /// Remove every queued purchase
+    pub fn clear_queue(&mut self) {
+        self.queue.clear();
+    }
+
+    /// Queued purchases in order
+    pub fn queue(&self) -> Vec<QueuedAction> {
+        self.queue.iter().copied().collect()
+    }
+}
@@ -1,33 +1,167 @@
+use crate::catalogue::{self, Catalogue, UnknownId};
+use crate::error::ActionError;
+use crate::modifiers::{Modifier, ModifierOp};
 use crate::resources::{res, res_ext, Resources};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
 
 /// Types of buildings available in the game
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
 pub enum BuildingType {
     /// Produces food
+    #[serde(alias = "Farm")]
     Farm,
     /// Produces wood passively
+    #[serde(alias = "LumberMill")]
     LumberMill,
     /// Produces stone passively
+    #[serde(alias = "Quarry")]
     Quarry,
     /// Produces iron passively
+    #[serde(alias = "Mine")]
     Mine,
     /// Converts food into gold
+    #[serde(alias = "Bakery")]
     Bakery,
     /// Generates energy
+    #[serde(alias = "Generator")]
     Generator,
     /// Produces science
+    #[serde(alias = "Lab")]
     Lab,
     /// Generates mana slowly
+    #[serde(alias = "Shrine")]
     Shrine,
+    /// Raises the population cap
+    #[serde(alias = "House")]
+    House,
+    /// Stores energy for the grid
+    #[serde(alias = "Battery")]
+    Battery,
+    /// Smelts stone into iron using energy
+    #[serde(alias = "Smelter")]
+    Smelter,
+    /// Forges iron into tools
+    #[serde(alias = "Forge")]
+    Forge,
+    /// Mints gold into coins using energy
+    #[serde(alias = "Mint")]
+    Mint,
 }
 
+impl BuildingType {
+    /// Every building type in display order
+    pub const ALL: [BuildingType; 13] = [
+        BuildingType::Farm,
+        BuildingType::LumberMill,
+        BuildingType::Quarry,
+        BuildingType::Mine,
+        BuildingType::Bakery,
+        BuildingType::Generator,
+        BuildingType::Lab,
+        BuildingType::Shrine,
+        BuildingType::House,
+        BuildingType::Battery,
+        BuildingType::Smelter,
+        BuildingType::Forge,
+        BuildingType::Mint,
+    ];
+}
+
+impl Catalogue for BuildingType {
+    const ENTRIES: &'static [Self] = &Self::ALL;
+
+    fn id(self) -> &'static str {
+        match self {
+            BuildingType::Farm => "farm",
+            BuildingType::LumberMill => "lumber_mill",
+            BuildingType::Quarry => "quarry",
+            BuildingType::Mine => "mine",
+            BuildingType::Bakery => "bakery",
+            BuildingType::Generator => "generator",
+            BuildingType::Lab => "lab",
+            BuildingType::Shrine => "shrine",
+            BuildingType::House => "house",
+            BuildingType::Battery => "battery",
+            BuildingType::Smelter => "smelter",
+            BuildingType::Forge => "forge",
+            BuildingType::Mint => "mint",
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            BuildingType::Farm => "Farm",
+            BuildingType::LumberMill => "Lumber Mill",
+            BuildingType::Quarry => "Quarry",
+            BuildingType::Mine => "Mine",
+            BuildingType::Bakery => "Bakery",
+            BuildingType::Generator => "Generator",
+            BuildingType::Lab => "Lab",
+            BuildingType::Shrine => "Shrine",
+            BuildingType::House => "House",
+            BuildingType::Battery => "Battery",
+            BuildingType::Smelter => "Smelter",
+            BuildingType::Forge => "Forge",
+            BuildingType::Mint => "Mint",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            BuildingType::Farm => "Grows food",
+            BuildingType::LumberMill => "Cuts wood",
+            BuildingType::Quarry => "Digs stone",
+            BuildingType::Mine => "Extracts iron",
+            BuildingType::Bakery => "Bakes food into gold",
+            BuildingType::Generator => "Burns iron to generate energy",
+            BuildingType::Lab => "Turns energy into science",
+            BuildingType::Shrine => "Gathers mana for a gold tithe",
+            BuildingType::House => "Raises the population cap",
+            BuildingType::Battery => "Stores surplus energy for the grid",
+            BuildingType::Smelter => "Smelts stone into iron using energy",
+            BuildingType::Forge => "Forges iron into tools",
+            BuildingType::Mint => "Mints gold into coins using energy",
+        }
+    }
+}
+
+impl FromStr for BuildingType {
+    type Err = UnknownId;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        catalogue::parse(s)
+    }
+}
+
+impl fmt::Display for BuildingType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+/// Building levels at which output doubles
+pub const MILESTONES: [u32; 3] = [25, 50, 100];
+
+/// Output multiplier from milestones reached at the given level
+pub fn milestone_multiplier(level: u32) -> f64 {
+    let reached = MILESTONES.iter().filter(|&&m| level >= m).count();
+    2f64.powi(reached as i32)
+}
+
+/// Fraction of a level's cost refunded when it is sold
+pub const SELL_REFUND_RATIO: f64 = 0.5;
+
 /// Static data for a building
 pub(crate) struct BuildingInfo {
     pub base_cost: Resources,
     pub growth: f64,
-    pub yield_per_tick: Resources,
+    pub yield_per_second: Resources,
+    pub workers: u32,
+    pub upkeep: Resources,
 }
 
 fn info(ty: BuildingType) -> BuildingInfo {
@@ -35,42 +169,102 @@ fn info(ty: BuildingType) -> BuildingInfo {
         BuildingType::Farm => BuildingInfo {
             base_cost: res(10.0, 10.0, 0.0, 0.0, 0.0),
             growth: 1.15,
-            yield_per_tick: res(0.0, 0.0, 1.0, 0.0, 0.0),
+            yield_per_second: res(0.0, 0.0, 1.0, 0.0, 0.0),
+            workers: 1,
+            upkeep: Resources::default(),
         },
         BuildingType::LumberMill => BuildingInfo {
             base_cost: res(15.0, 5.0, 0.0, 0.0, 0.0),
             growth: 1.15,
-            yield_per_tick: res(1.0, 0.0, 0.0, 0.0, 0.0),
+            yield_per_second: res(1.0, 0.0, 0.0, 0.0, 0.0),
+            workers: 1,
+            upkeep: Resources::default(),
         },
         BuildingType::Quarry => BuildingInfo {
             base_cost: res(5.0, 15.0, 0.0, 0.0, 0.0),
             growth: 1.15,
-            yield_per_tick: res(0.0, 1.0, 0.0, 0.0, 0.0),
+            yield_per_second: res(0.0, 1.0, 0.0, 0.0, 0.0),
+            workers: 1,
+            upkeep: Resources::default(),
         },
         BuildingType::Mine => BuildingInfo {
             base_cost: res(20.0, 20.0, 0.0, 0.0, 0.0),
             growth: 1.2,
-            yield_per_tick: res(0.0, 0.0, 0.0, 1.0, 0.0),
+            yield_per_second: res(0.0, 0.0, 0.0, 1.0, 0.0),
+            workers: 2,
+            upkeep: Resources::default(),
         },
         BuildingType::Bakery => BuildingInfo {
             base_cost: res(50.0, 25.0, 100.0, 10.0, 0.0),
             growth: 1.2,
-            yield_per_tick: res(0.0, 0.0, -1.0, 0.0, 0.2),
+            yield_per_second: res(0.0, 0.0, -1.0, 0.0, 0.2),
+            workers: 1,
+            upkeep: Resources::default(),
         },
         BuildingType::Generator => BuildingInfo {
             base_cost: res(30.0, 20.0, 0.0, 5.0, 10.0),
             growth: 1.25,
-            yield_per_tick: res_ext(0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0),
+            yield_per_second: res_ext(0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0),
+            workers: 1,
+            upkeep: res(0.0, 0.0, 0.0, 0.1, 0.0),
         },
         BuildingType::Lab => BuildingInfo {
             base_cost: res(50.0, 50.0, 0.0, 10.0, 20.0),
             growth: 1.25,
-            yield_per_tick: res_ext(0.0, 0.0, 0.0, 0.0, 0.0, -0.5, 0.5, 0.0),
+            yield_per_second: res_ext(0.0, 0.0, 0.0, 0.0, 0.0, -0.5, 0.5, 0.0),
+            workers: 2,
+            upkeep: Resources::default(),
         },
         BuildingType::Shrine => BuildingInfo {
             base_cost: res(100.0, 100.0, 0.0, 50.0, 0.0),
             growth: 1.3,
-            yield_per_tick: res_ext(0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.2),
+            yield_per_second: res_ext(0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.2),
+            workers: 1,
+            upkeep: res(0.0, 0.0, 0.0, 0.0, 0.1),
+        },
+        BuildingType::House => BuildingInfo {
+            base_cost: res(25.0, 10.0, 0.0, 0.0, 0.0),
+            growth: 1.2,
+            yield_per_second: Resources::default(),
+            workers: 0,
+            upkeep: Resources::default(),
+        },
+        BuildingType::Battery => BuildingInfo {
+            base_cost: res(40.0, 40.0, 0.0, 20.0, 10.0),
+            growth: 1.3,
+            yield_per_second: Resources::default(),
+            workers: 0,
+            upkeep: Resources::default(),
+        },
+        BuildingType::Smelter => BuildingInfo {
+            base_cost: res(80.0, 120.0, 0.0, 30.0, 0.0),
+            growth: 1.25,
+            yield_per_second: res_ext(0.0, -2.0, 0.0, 1.0, 0.0, -0.5, 0.0, 0.0),
+            workers: 2,
+            upkeep: Resources::default(),
+        },
+        BuildingType::Forge => BuildingInfo {
+            base_cost: res(60.0, 60.0, 0.0, 80.0, 20.0),
+            growth: 1.25,
+            yield_per_second: Resources {
+                iron: -2.0,
+                tools: 0.5,
+                ..Resources::default()
+            },
+            workers: 2,
+            upkeep: Resources::default(),
+        },
+        BuildingType::Mint => BuildingInfo {
+            base_cost: res(50.0, 150.0, 0.0, 50.0, 200.0),
+            growth: 1.3,
+            yield_per_second: Resources {
+                gold: -1.0,
+                energy: -0.5,
+                coins: 0.1,
+                ..Resources::default()
+            },
+            workers: 2,
+            upkeep: Resources::default(),
         },
     }
 }
@@ -82,9 +276,12 @@ fn cost_for(ty: BuildingType, level: u32) -> Resources {
 }
 
 /// Player owned buildings
-#[derive(Default, Serialize, Deserialize)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct Buildings {
     levels: HashMap<BuildingType, u32>,
+    /// Levels switched off by the player
+    #[serde(default)]
+    paused: HashMap<BuildingType, u32>,
 }
 
 impl Buildings {
@@ -93,6 +290,24 @@ impl Buildings {
         *self.levels.get(&ty).unwrap_or(&0)
     }
 
+    /// Number of levels currently running
+    pub fn active(&self, ty: BuildingType) -> u32 {
+        self.level(ty) - self.paused.get(&ty).copied().unwrap_or(0)
+    }
+
+    /// Run `count` levels of a building and pause the rest. Returns the
+    /// resulting active count.
+    pub fn set_active(&mut self, ty: BuildingType, count: u32) -> u32 {
+        let level = self.level(ty);
+        let count = count.min(level);
+        if count == level {
+            self.paused.remove(&ty);
+        } else {
+            self.paused.insert(ty, level - count);
+        }
+        count
+    }
+
     /// Cost to build next level
     pub fn cost(&self, ty: BuildingType) -> Resources {
         cost_for(ty, self.level(ty))
@@ -110,27 +325,62 @@ impl Buildings {
         if *entry == 0 {
             self.levels.remove(&ty);
         }
+        // Paused levels are removed first so running ones keep working
+        if let Some(paused) = self.paused.get_mut(&ty) {
+            *paused = paused.saturating_sub(amount);
+            if *paused == 0 {
+                self.paused.remove(&ty);
+            }
+        }
     }
 
-    /// Total yield per tick of all buildings
-    pub fn total_yield(&self) -> Resources {
-        let mut r = Resources::default();
-        for (&ty, &level) in &self.levels {
-            let info = info(ty);
-            r.add(&info.yield_per_tick.scale(level as f64));
-        }
-        r
+    /// Yield per second of `count` levels of a building
+    pub fn yield_of(ty: BuildingType, count: u32) -> Resources {
+        info(ty).yield_per_second.scale(count as f64)
+    }
+
+    /// Upkeep per second of `count` levels of a building
+    pub fn upkeep_of(ty: BuildingType, count: u32) -> Resources {
+        info(ty).upkeep.scale(count as f64)
+    }
+
+    /// Output modifiers from level milestones reached
+    pub fn milestone_modifiers(&self) -> Vec<Modifier> {
+        BuildingType::ALL
+            .iter()
+            .map(|&ty| (ty, milestone_multiplier(self.level(ty))))
+            .filter(|&(_, m)| m > 1.0)
+            .map(|(ty, m)| Modifier::building("Milestones", ty, ModifierOp::Multiplicative(m)))
+            .collect()
+    }
+
+    /// Workers needed to run one level of a building
+    pub fn workers_per_level(ty: BuildingType) -> u32 {
+        info(ty).workers
     }
 
     /// Attempt to build if resources are sufficient
-    pub fn build(&mut self, ty: BuildingType, res: &mut Resources) -> bool {
+    pub fn build(&mut self, ty: BuildingType, res: &mut Resources) -> Result<(), ActionError> {
         let cost = self.cost(ty);
-        if res.subtract(&cost) {
-            self.increment(ty);
-            true
-        } else {
-            false
+        if !res.subtract(&cost) {
+            return Err(ActionError::Insufficient {
+                missing: res.shortfall(&cost),
+            });
+        }
+        self.increment(ty);
+        Ok(())
+    }
+
+    /// Sell up to `amount` levels refunding `ratio` of the cost each level
+    /// was bought for. Returns the number of levels sold.
+    pub fn sell(&mut self, ty: BuildingType, amount: u32, res: &mut Resources, ratio: f64) -> u32 {
+        let sold = amount.min(self.level(ty));
+        for _ in 0..sold {
+            let level = self.level(ty);
+            res.add(&cost_for(ty, level - 1).scale(ratio));
+            self.decrement(ty, 1);
         }
+        sold
     }
 
     /// Public helper for tests
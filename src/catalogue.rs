@@ -0,0 +1,114 @@
+use serde::Serialize;
+use std::fmt;
+
+/// A name that matches no catalogue entry
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownId(pub String);
+
+impl fmt::Display for UnknownId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown id '{}'", self.0)
+    }
+}
+
+impl std::error::Error for UnknownId {}
+
+/// Static, player-facing description of a kind of game content. Implementors
+/// serialize as their id and keep PascalCase serde aliases so older saves load.
+pub trait Catalogue: Copy + 'static {
+    /// Every entry in display order
+    const ENTRIES: &'static [Self];
+
+    /// Identifier used by the JS API and in saves
+    fn id(self) -> &'static str;
+
+    /// Name shown to the player
+    fn name(self) -> &'static str;
+
+    /// One-line explanation shown to the player
+    fn description(self) -> &'static str;
+}
+
+/// Look up an entry by its id
+pub fn parse<T: Catalogue>(id: &str) -> Result<T, UnknownId> {
+    T::ENTRIES
+        .iter()
+        .copied()
+        .find(|entry| entry.id() == id)
+        .ok_or_else(|| UnknownId(id.to_string()))
+}
+
+/// Catalogue entry as returned to JS
+#[derive(Serialize)]
+pub struct CatalogueEntry {
+    /// Identifier used by the JS API and in saves
+    pub id: &'static str,
+    /// Name shown to the player
+    pub name: &'static str,
+    /// One-line explanation shown to the player
+    pub description: &'static str,
+}
+
+/// Describe every entry of a catalogue
+pub fn entries<T: Catalogue>() -> Vec<CatalogueEntry> {
+    T::ENTRIES
+        .iter()
+        .map(|&entry| CatalogueEntry {
+            id: entry.id(),
+            name: entry.name(),
+            description: entry.description(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buildings::BuildingType;
+    use crate::recipes::Recipe;
+    use crate::research::Tech;
+    use crate::resources::ResourceKind;
+    use crate::spells::Spell;
+    use crate::upgrades::UpgradeType;
+    use serde::de::DeserializeOwned;
+    use std::str::FromStr;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    fn round_trips<T>()
+    where
+        T: Catalogue + FromStr<Err = UnknownId> + fmt::Display + Serialize + DeserializeOwned,
+        T: PartialEq + fmt::Debug,
+    {
+        for &entry in T::ENTRIES {
+            assert_eq!(entry.to_string(), entry.id());
+            assert_eq!(entry.id().parse::<T>(), Ok(entry));
+            let json = serde_json::to_string(&entry).unwrap();
+            assert_eq!(json, format!("\"{}\"", entry.id()));
+            assert_eq!(serde_json::from_str::<T>(&json).unwrap(), entry);
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn ids_round_trip() {
+        round_trips::<BuildingType>();
+        round_trips::<Tech>();
+        round_trips::<UpgradeType>();
+        round_trips::<ResourceKind>();
+        round_trips::<Spell>();
+        round_trips::<Recipe>();
+        assert_eq!(
+            "castle".parse::<BuildingType>(),
+            Err(UnknownId("castle".into()))
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn old_variant_names_still_load() {
+        let ty: BuildingType = serde_json::from_str("\"LumberMill\"").unwrap();
+        assert_eq!(ty, BuildingType::LumberMill);
+        let tech: Tech = serde_json::from_str("\"Mining\"").unwrap();
+        assert_eq!(tech, Tech::Mining);
+        let spell: Spell = serde_json::from_str("\"TimeWarp\"").unwrap();
+        assert_eq!(spell, Spell::TimeWarp);
+    }
+}
@@ -0,0 +1,131 @@
+use crate::resources::Resources;
+use crate::snapshot::{BuildingSnapshot, TechSnapshot};
+use serde::Serialize;
+
+/// Parts of the game state the UI re-renders independently
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Section {
+    /// Resource amounts and rates
+    Resources,
+    /// Building counts, costs and locks
+    Buildings,
+    /// Technologies and their costs
+    Research,
+    /// Unlocked achievements
+    Achievements,
+}
+
+impl Section {
+    /// Every tracked section
+    pub const ALL: [Section; 4] = [
+        Section::Resources,
+        Section::Buildings,
+        Section::Research,
+        Section::Achievements,
+    ];
+}
+
+/// One bit per cost in `costs`, set when `resources` cover it
+pub fn affordable_mask(costs: impl Iterator<Item = Resources>, resources: &Resources) -> u64 {
+    costs
+        .enumerate()
+        .filter(|(_, cost)| resources.can_afford(cost))
+        .fold(0, |mask, (i, _)| mask | 1 << i)
+}
+
+/// Remembers the version at which each section last changed. Mutations
+/// flag the sections they touch, and the flags are turned into a new version
+/// when the UI asks.
+#[derive(Clone)]
+pub struct ChangeTracker {
+    version: u32,
+    dirty: [bool; 4],
+    changed_at: [u32; 4],
+    affordable: [Option<u64>; 4],
+}
+
+impl Default for ChangeTracker {
+    /// A fresh tracker reports every section once
+    fn default() -> Self {
+        ChangeTracker {
+            version: 0,
+            dirty: [true; 4],
+            changed_at: [0; 4],
+            affordable: [None; 4],
+        }
+    }
+}
+
+impl ChangeTracker {
+    /// Latest version handed out
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Flag a section as changed
+    pub fn mark(&mut self, section: Section) {
+        self.dirty[section as usize] = true;
+    }
+
+    /// Flag every section as changed
+    pub fn mark_all(&mut self) {
+        self.dirty = [true; 4];
+        self.affordable = [None; 4];
+    }
+
+    /// Flag a section as changed if the set of entries the player can
+    /// afford, one bit per entry, differs from the last one recorded
+    pub fn mark_affordable(&mut self, section: Section, mask: u64) {
+        let last = &mut self.affordable[section as usize];
+        if *last != Some(mask) {
+            *last = Some(mask);
+            self.mark(section);
+        }
+    }
+
+    /// Start a new version if any section was flagged since the last one
+    pub fn commit(&mut self) {
+        if !self.dirty.contains(&true) {
+            return;
+        }
+        self.version += 1;
+        for (dirty, changed_at) in self.dirty.iter_mut().zip(&mut self.changed_at) {
+            if std::mem::take(dirty) {
+                *changed_at = self.version;
+            }
+        }
+    }
+
+    /// Whether a section changed after the given version
+    pub fn changed_since(&self, section: Section, version: u32) -> bool {
+        self.changed_at[section as usize] > version
+    }
+}
+
+/// Resource amounts together with their rates
+#[derive(Serialize)]
+pub struct ResourceSection {
+    /// Current stock of every resource
+    pub amounts: Resources,
+    /// Net change of every resource per second
+    pub rates: Resources,
+}
+
+/// Sections that changed since a version, as returned to JS
+#[derive(Serialize)]
+pub struct Changes {
+    /// Version to pass to the next query
+    pub version: u32,
+    /// Resource amounts and rates, if either changed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resources: Option<ResourceSection>,
+    /// Every building, if any of them changed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub buildings: Option<Vec<BuildingSnapshot>>,
+    /// Every technology, if any of them changed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub research: Option<Vec<TechSnapshot>>,
+    /// Unlocked achievements, if a new one was unlocked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub achievements: Option<Vec<String>>,
+}
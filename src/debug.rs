@@ -0,0 +1,77 @@
+//! Time controls and cheats for QA and balance testing. Only compiled with
+//! the `debug-tools` feature.
+
+use crate::changes::Section;
+use crate::resources::ResourceKind;
+use crate::{GameState, MAX_OFFLINE_SECONDS};
+
+impl GameState {
+    /// Run `seconds` of game time immediately, at most
+    /// `MAX_OFFLINE_SECONDS`. NaN runs nothing.
+    pub fn fast_forward(&mut self, seconds: f64) {
+        if seconds.is_nan() {
+            return;
+        }
+        let seconds = seconds.clamp(0.0, MAX_OFFLINE_SECONDS);
+        self.run_ticks((seconds / self.tick_rate).floor() as u64);
+    }
+
+    /// Run exactly one tick
+    pub fn step(&mut self) {
+        self.run_ticks(1);
+    }
+
+    /// Add `amount` of a resource by name. Returns false if the name is
+    /// unknown or the amount is negative or not finite.
+    pub fn grant(&mut self, name: String, amount: f64) -> bool {
+        match name.parse::<ResourceKind>() {
+            Ok(kind) if amount.is_finite() && amount >= 0.0 => {
+                *self.resources.get_mut(kind) += amount;
+                self.mark_changed(&[Section::Resources]);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buildings::BuildingType;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn time_controls() {
+        let mut g = GameState::new();
+        g.event_chance = 0.0;
+        g.buildings.increment(BuildingType::Farm);
+        g.resources.food = 10.0;
+        g.fast_forward(10.0);
+        g.step();
+        assert_eq!(g.resources.food, 15.5);
+        g.fast_forward(f64::NAN);
+        g.fast_forward(-5.0);
+        assert_eq!(g.resources.food, 15.5);
+        g.frozen = true;
+        g.tick(0.0);
+        g.tick(100.0);
+        assert_eq!(g.resources.food, 15.5);
+        assert!(g.grant("gold".into(), 5.0));
+        assert!(!g.grant("diamonds".into(), 5.0));
+        assert!(!g.grant("gold".into(), f64::NAN));
+        assert!(!g.grant("gold".into(), -1.0));
+        assert_eq!(g.resources.gold, 5.0);
+    }
+    #[wasm_bindgen_test]
+    fn fast_forward_is_capped() {
+        let mut g = GameState::new();
+        g.event_chance = 0.0;
+        g.tick_rate = 10.0;
+        g.buildings.increment(BuildingType::Farm);
+        let mut capped = g.clone();
+        g.fast_forward(f64::INFINITY);
+        capped.fast_forward(MAX_OFFLINE_SECONDS);
+        assert_eq!(g.resources.food, capped.resources.food);
+    }
+}
@@ -0,0 +1,171 @@
+use crate::modifiers::{Modifier, ModifierOp};
+use crate::resources::ResourceKind;
+use serde::{Deserialize, Serialize};
+
+/// Temporary buffs and debuffs
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum EffectType {
+    /// +50% food for 60s
+    HarvestFestival,
+    /// Halves food for 30s
+    Drought,
+    /// Shields farms from storms for 120s
+    Ward,
+    /// +25% output of every building for 60s
+    Blessing,
+}
+
+/// How a new instance of an effect combines with one already active
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stacking {
+    /// Restart the timer at the full duration
+    Refresh,
+    /// Add the full duration to the time remaining
+    Extend,
+}
+
+struct EffectInfo {
+    name: &'static str,
+    duration: f64,
+    stacking: Stacking,
+    resource: Option<ResourceKind>,
+    op: Option<ModifierOp>,
+}
+
+fn info(effect: EffectType) -> EffectInfo {
+    match effect {
+        EffectType::HarvestFestival => EffectInfo {
+            name: "Harvest festival",
+            duration: 60.0,
+            stacking: Stacking::Refresh,
+            resource: Some(ResourceKind::Food),
+            op: Some(ModifierOp::Additive(0.5)),
+        },
+        EffectType::Drought => EffectInfo {
+            name: "Drought",
+            duration: 30.0,
+            stacking: Stacking::Extend,
+            resource: Some(ResourceKind::Food),
+            op: Some(ModifierOp::Multiplicative(0.5)),
+        },
+        EffectType::Ward => EffectInfo {
+            name: "Ward",
+            duration: 120.0,
+            stacking: Stacking::Refresh,
+            resource: None,
+            op: None,
+        },
+        EffectType::Blessing => EffectInfo {
+            name: "Blessing",
+            duration: 60.0,
+            stacking: Stacking::Refresh,
+            resource: None,
+            op: Some(ModifierOp::Additive(0.25)),
+        },
+    }
+}
+
+impl EffectType {
+    /// Name shown to the player
+    pub fn name(self) -> &'static str {
+        info(self).name
+    }
+}
+
+/// An effect currently running
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ActiveEffect {
+    /// Which effect
+    pub effect: EffectType,
+    /// Game time left in seconds
+    pub remaining: f64,
+}
+
+/// Timed effects currently applied to the game
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct Effects {
+    active: Vec<ActiveEffect>,
+}
+
+impl Effects {
+    /// Start an effect following its stacking rule
+    pub fn add(&mut self, effect: EffectType) {
+        let i = info(effect);
+        let existing = self.active.iter_mut().find(|a| a.effect == effect);
+        match (existing, i.stacking) {
+            (Some(a), Stacking::Refresh) => a.remaining = a.remaining.max(i.duration),
+            (Some(a), Stacking::Extend) => a.remaining += i.duration,
+            (None, _) => self.active.push(ActiveEffect {
+                effect,
+                remaining: i.duration,
+            }),
+        }
+    }
+
+    /// Whether any instance of an effect is running
+    pub fn is_active(&self, effect: EffectType) -> bool {
+        self.active.iter().any(|a| a.effect == effect)
+    }
+
+    /// Count down all effects by elapsed game time. Returns the effects
+    /// that ran out.
+    pub fn advance(&mut self, seconds: f64) -> Vec<EffectType> {
+        let mut expired = Vec::new();
+        self.active.retain_mut(|a| {
+            a.remaining -= seconds;
+            if a.remaining <= 1e-9 {
+                expired.push(a.effect);
+                false
+            } else {
+                true
+            }
+        });
+        expired
+    }
+
+    /// Output modifiers from running effects
+    pub fn modifiers(&self) -> Vec<Modifier> {
+        self.active
+            .iter()
+            .filter_map(|a| {
+                let i = info(a.effect);
+                Some(Modifier {
+                    source: format!("{} effect", i.name),
+                    building: None,
+                    resource: i.resource,
+                    op: i.op?,
+                })
+            })
+            .collect()
+    }
+
+    /// Running effects
+    pub fn list(&self) -> Vec<ActiveEffect> {
+        self.active.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn stacking_rules() {
+        let mut e = Effects::default();
+        e.add(EffectType::HarvestFestival);
+        e.advance(20.0);
+        e.add(EffectType::HarvestFestival);
+        e.add(EffectType::Drought);
+        e.add(EffectType::Drought);
+        let list = e.list();
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].remaining, 60.0);
+        assert_eq!(list[1].remaining, 60.0);
+        assert_eq!(
+            e.advance(60.0),
+            [EffectType::HarvestFestival, EffectType::Drought]
+        );
+        assert!(e.list().is_empty());
+    }
+}
@@ -0,0 +1,103 @@
+use crate::catalogue::{Catalogue, UnknownId};
+use crate::research::Tech;
+use crate::resources::{ResourceKind, Resources};
+use serde::Serialize;
+use std::fmt;
+
+/// Why a player action was refused
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ActionError {
+    /// The name matches no known building, technology or upgrade
+    UnknownId {
+        /// Name as given
+        id: String,
+    },
+    /// A technology must be researched first
+    Locked {
+        /// Technology still to research
+        requires: Tech,
+    },
+    /// Resources fall short of the cost by the given amounts
+    Insufficient {
+        /// Amount of each resource still needed
+        missing: Resources,
+    },
+    /// The technology has already been researched
+    AlreadyOwned,
+}
+
+impl From<UnknownId> for ActionError {
+    fn from(err: UnknownId) -> Self {
+        ActionError::UnknownId { id: err.0 }
+    }
+}
+
+impl fmt::Display for ActionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActionError::UnknownId { id } => write!(f, "Unknown id '{id}'"),
+            ActionError::Locked { requires } => write!(f, "Requires {}", requires.name()),
+            ActionError::Insufficient { missing } => {
+                let parts: Vec<String> = ResourceKind::ALL
+                    .iter()
+                    .filter(|&&kind| missing.get(kind) > 0.0)
+                    .map(|&kind| format!("{:.1} {}", missing.get(kind), kind.name()))
+                    .collect();
+                write!(f, "Need {} more", parts.join(", "))
+            }
+            ActionError::AlreadyOwned => f.write_str("Already owned"),
+        }
+    }
+}
+
+impl std::error::Error for ActionError {}
+
+/// Outcome of a player action as returned to JS
+#[derive(Serialize)]
+pub struct ActionOutcome {
+    /// Whether the action went through
+    pub ok: bool,
+    /// Why it was refused
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ActionError>,
+    /// The refusal as a sentence for the player
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl From<Result<(), ActionError>> for ActionOutcome {
+    fn from(result: Result<(), ActionError>) -> Self {
+        match result {
+            Ok(()) => ActionOutcome {
+                ok: true,
+                error: None,
+                message: None,
+            },
+            Err(err) => ActionOutcome {
+                ok: false,
+                message: Some(err.to_string()),
+                error: Some(err),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::res;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn insufficient_lists_missing_amounts() {
+        let err = ActionError::Insufficient {
+            missing: res(0.0, 2.5, 0.0, 0.0, 0.0),
+        };
+        assert_eq!(err.to_string(), "Need 2.5 Stone more");
+        let json = serde_json::to_value(ActionOutcome::from(Err(err))).unwrap();
+        assert_eq!(json["ok"], false);
+        assert_eq!(json["error"]["kind"], "insufficient");
+        assert_eq!(json["error"]["missing"]["stone"], 2.5);
+    }
+}
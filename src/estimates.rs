@@ -0,0 +1,85 @@
+use crate::resources::{ResourceKind, Resources};
+use serde::Serialize;
+
+/// How long until a cost can be paid at current rates
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Estimate {
+    /// Seconds until affordable, or `None` if a missing resource is not
+    /// being produced
+    pub seconds: Option<f64>,
+    /// Missing resource that takes longest to accumulate
+    pub bottleneck: Option<ResourceKind>,
+}
+
+/// Estimate the wait for `cost` given the current stock and net rate per
+/// second of each resource
+pub fn time_to_afford(stock: &Resources, rate: &Resources, cost: &Resources) -> Estimate {
+    let mut worst = 0.0;
+    let mut bottleneck = None;
+    for kind in ResourceKind::ALL {
+        let missing = cost.get(kind) - stock.get(kind);
+        if missing <= 0.0 {
+            continue;
+        }
+        let wait = if rate.get(kind) > 0.0 {
+            missing / rate.get(kind)
+        } else {
+            f64::INFINITY
+        };
+        if bottleneck.is_none() || wait > worst {
+            worst = wait;
+            bottleneck = Some(kind);
+        }
+    }
+    Estimate {
+        seconds: worst.is_finite().then_some(worst),
+        bottleneck,
+    }
+}
+
+/// Wait for one building, upgrade or technology
+#[derive(Serialize)]
+pub struct CostEstimate {
+    /// Identifier used by the JS API
+    pub id: &'static str,
+    /// Name shown to the player
+    pub name: &'static str,
+    /// Cost of the next level or of the technology
+    pub cost: Resources,
+    /// Time until the cost can be paid
+    #[serde(flatten)]
+    pub estimate: Estimate,
+}
+
+/// Waits for everything the player can buy
+#[derive(Serialize)]
+pub struct AffordEstimates {
+    /// Every building type
+    pub buildings: Vec<CostEstimate>,
+    /// Every upgrade
+    pub upgrades: Vec<CostEstimate>,
+    /// Technologies not yet researched
+    pub research: Vec<CostEstimate>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::res;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn slowest_resource_is_bottleneck() {
+        let stock = res(10.0, 0.0, 0.0, 0.0, 0.0);
+        let rate = res(1.0, 0.5, 0.0, 0.0, 0.0);
+        let est = time_to_afford(&stock, &rate, &res(20.0, 2.0, 0.0, 0.0, 0.0));
+        assert_eq!(est.seconds, Some(10.0));
+        assert_eq!(est.bottleneck, Some(ResourceKind::Wood));
+        let est = time_to_afford(&stock, &rate, &res(5.0, 0.0, 1.0, 0.0, 0.0));
+        assert_eq!(est.seconds, None);
+        assert_eq!(est.bottleneck, Some(ResourceKind::Food));
+        let est = time_to_afford(&stock, &rate, &res(5.0, 0.0, 0.0, 0.0, 0.0));
+        assert_eq!(est.seconds, Some(0.0));
+        assert_eq!(est.bottleneck, None);
+    }
+}
@@ -1,22 +1,44 @@
 use crate::buildings::{BuildingType, Buildings};
+use crate::effects::{EffectType, Effects};
 use crate::resources::Resources;
 use rand::Rng;
 
-/// Chance of farm loss event each tick
+/// Chance of farm loss event each second
 pub const FARM_LOSS_CHANCE: f64 = 0.05;
 
+/// Chance that an event with the given chance per second happens at least
+/// once within `seconds`
+pub fn chance_within(per_second: f64, seconds: f64) -> f64 {
+    1.0 - (1.0 - per_second).powf(seconds)
+}
+
 /// Checks for random events and applies effects. Returns a message when an
 /// event occurs.
 pub fn check_random_events<R: Rng>(
     buildings: &mut Buildings,
     resources: &mut Resources,
+    effects: &mut Effects,
     rng: &mut R,
     chance: f64,
 ) -> Option<String> {
-    if rng.gen_bool(0.5) {
-        farm_loss_event(buildings, rng, chance)
+    let msg = if rng.random_bool(0.5) {
+        // Warded farms are safe from storms
+        if effects.is_active(EffectType::Ward) {
+            None
+        } else {
+            farm_loss_event(buildings, rng, chance)
+        }
     } else {
         treasure_event(resources, rng, chance)
+    };
+    if msg.is_some() {
+        return msg;
+    }
+    // Seasonal events roll separately so storms and treasure keep their odds
+    if rng.random_bool(0.5) {
+        festival_event(effects, rng, chance)
+    } else {
+        drought_event(effects, rng, chance)
     }
 }
 
@@ -26,7 +48,7 @@ pub fn farm_loss_event<R: Rng>(
     rng: &mut R,
     chance: f64,
 ) -> Option<String> {
-    if rng.gen_bool(chance) {
+    if rng.random_bool(chance) {
         let farms = buildings.level(BuildingType::Farm);
         if farms > 0 {
             let mut loss = ((farms as f64) * 0.1).ceil() as u32;
@@ -42,14 +64,32 @@ pub fn farm_loss_event<R: Rng>(
 
 /// Random treasure awarding gold
 pub fn treasure_event<R: Rng>(res: &mut Resources, rng: &mut R, chance: f64) -> Option<String> {
-    if rng.gen_bool(chance * 0.5) {
-        let gold = rng.gen_range(5..20) as f64;
+    if rng.random_bool(chance * 0.5) {
+        let gold = rng.random_range(5..20) as f64;
         res.gold += gold;
         return Some(format!("Found a hidden treasure worth {gold} gold!"));
     }
     None
 }
 
+/// Harvest festival temporarily boosting food
+pub fn festival_event<R: Rng>(effects: &mut Effects, rng: &mut R, chance: f64) -> Option<String> {
+    if rng.random_bool(chance * 0.5) {
+        effects.add(EffectType::HarvestFestival);
+        return Some("A harvest festival boosts food production!".to_string());
+    }
+    None
+}
+
+/// Drought temporarily cutting food
+pub fn drought_event<R: Rng>(effects: &mut Effects, rng: &mut R, chance: f64) -> Option<String> {
+    if rng.random_bool(chance * 0.5) {
+        effects.add(EffectType::Drought);
+        return Some("A drought withers the crops!".to_string());
+    }
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -68,4 +108,44 @@ mod tests {
         assert!(msg.contains("storm"));
         assert_eq!(b.level(BuildingType::Farm), 9); // ceil(10% of 10) = 1
     }
+
+    #[wasm_bindgen_test]
+    fn chance_scales_with_duration() {
+        assert!((chance_within(0.05, 1.0) - 0.05).abs() < 1e-12);
+        let short = chance_within(0.05, 0.2);
+        assert!((1.0 - (1.0 - short).powi(5) - 0.05).abs() < 1e-12);
+        assert_eq!(chance_within(1.0, 0.2), 1.0);
+    }
+
+    #[wasm_bindgen_test]
+    fn storms_keep_their_odds() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut storms = 0;
+        for _ in 0..1000 {
+            let mut b = Buildings::default();
+            b.increment(BuildingType::Farm);
+            let mut res = Resources::default();
+            let mut effects = Effects::default();
+            check_random_events(&mut b, &mut res, &mut effects, &mut rng, 1.0);
+            if b.level(BuildingType::Farm) == 0 {
+                storms += 1;
+            }
+        }
+        // Half of all rolls still pick a storm
+        assert!((450..550).contains(&storms));
+    }
+
+    #[wasm_bindgen_test]
+    fn ward_blocks_storms() {
+        let mut b = Buildings::default();
+        b.increment(BuildingType::Farm);
+        let mut res = Resources::default();
+        let mut effects = Effects::default();
+        effects.add(EffectType::Ward);
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..100 {
+            check_random_events(&mut b, &mut res, &mut effects, &mut rng, 1.0);
+        }
+        assert_eq!(b.level(BuildingType::Farm), 1);
+    }
 }
@@ -1,130 +1,470 @@
 //! Re-export modules and provide wasm bindings
 
 mod achievements;
+mod automation;
 mod buildings;
+mod catalogue;
+mod changes;
+#[cfg(feature = "debug-tools")]
+mod debug;
+mod effects;
+mod error;
+mod estimates;
 mod events;
+mod market;
+mod modifiers;
+mod population;
+mod power;
 mod prestige;
+mod production;
+mod quests;
+mod recipes;
 mod research;
 mod resources;
+mod snapshot;
+mod spells;
+mod statistics;
 mod systems;
 mod upgrades;
 
 pub use achievements::*;
+pub use automation::*;
 pub use buildings::*;
+pub use catalogue::*;
+pub use changes::*;
+pub use effects::*;
+pub use error::*;
+pub use estimates::*;
 pub use events::*;
+pub use market::*;
+pub use modifiers::*;
+pub use population::*;
+pub use power::*;
 pub use prestige::*;
+pub use production::*;
+pub use quests::*;
+pub use recipes::*;
 pub use research::*;
 pub use resources::*;
+pub use snapshot::*;
+pub use spells::*;
+pub use statistics::*;
 pub use systems::*;
 pub use upgrades::*;
 
+#[cfg(all(feature = "debug-tools", not(debug_assertions)))]
+compile_error!("the debug-tools feature is for development builds only");
+
 use std::cell::RefCell;
 use wasm_bindgen::prelude::*;
 
 thread_local! {
-    static GAME: RefCell<GameState> = RefCell::new(GameState::new());
+    static DEFAULT: RefCell<Game> = RefCell::new(Game::new());
 }
 
-/// JS facing game API
-#[wasm_bindgen]
-pub struct Game;
+/// Serialize the result of a player action for JS
+fn outcome_json(result: Result<(), ActionError>) -> String {
+    serde_json::to_string(&ActionOutcome::from(result)).expect("serialize outcome")
+}
+
+/// JS facing game API. Each instance owns its own state, so several games
+/// can run side by side. Extending `SharedGame` keeps its statics callable
+/// as `Game.tick(now)` and so on from JS.
+#[wasm_bindgen(extends = SharedGame)]
+pub struct Game {
+    state: GameState,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Game::new()
+    }
+}
 
 #[wasm_bindgen]
 impl Game {
-    /// Create a new game instance
+    /// Create a new game instance with fresh state
     #[wasm_bindgen(constructor)]
     pub fn new() -> Game {
-        Game
+        Game {
+            // Field added by `extends`, backing the `SharedGame` prototype
+            parent: SharedGame.into(),
+            state: GameState::new(),
+        }
+    }
+
+    /// Ids, names and descriptions of every building as a JSON string
+    pub fn list_buildings() -> String {
+        serde_json::to_string(&entries::<BuildingType>()).expect("serialize buildings")
+    }
+
+    /// Ids, names and descriptions of every technology as a JSON string
+    pub fn list_techs() -> String {
+        serde_json::to_string(&entries::<Tech>()).expect("serialize techs")
     }
 
+    /// Ids, names and descriptions of every upgrade as a JSON string
+    pub fn list_upgrades() -> String {
+        serde_json::to_string(&entries::<UpgradeType>()).expect("serialize upgrades")
+    }
+
+    /// Ids, names and descriptions of every resource as a JSON string
+    pub fn list_resources() -> String {
+        serde_json::to_string(&entries::<ResourceKind>()).expect("serialize resources")
+    }
+
+    /// Ids, names and descriptions of every spell as a JSON string
+    pub fn list_spells() -> String {
+        serde_json::to_string(&entries::<Spell>()).expect("serialize spells")
+    }
+
+    /// Ids, names and descriptions of every recipe as a JSON string
+    pub fn list_recipes() -> String {
+        serde_json::to_string(&entries::<Recipe>()).expect("serialize recipes")
+    }
+}
+
+/// Static API over one default game, kept for pages written against the
+/// old singleton `Game`. `game_api!` mirrors every `Game` method here, and
+/// `Game` inherits these statics in JS.
+#[wasm_bindgen]
+pub struct SharedGame;
+
+/// Define methods on `Game` and mirror each as a `SharedGame` static that
+/// calls it on the default game, so the two APIs cannot drift apart
+macro_rules! game_api {
+    (@ [$($game:tt)*] [$($shared:tt)*]) => {
+        #[wasm_bindgen]
+        impl Game {
+            $($game)*
+        }
+
+        #[wasm_bindgen]
+        impl SharedGame {
+            $($shared)*
+        }
+    };
+    (@ [$($game:tt)*] [$($shared:tt)*]
+        $(#[$attr:meta])*
+        pub fn $name:ident(&mut $self:ident $(, $arg:ident: $ty:ty)*) $(-> $ret:ty)? $body:block
+        $($rest:tt)*
+    ) => {
+        game_api!(@
+            [$($game)* $(#[$attr])* pub fn $name(&mut $self $(, $arg: $ty)*) $(-> $ret)? $body]
+            [$($shared)* $(#[$attr])* pub fn $name($($arg: $ty),*) $(-> $ret)? {
+                DEFAULT.with(|g| g.borrow_mut().$name($($arg),*))
+            }]
+            $($rest)*
+        );
+    };
+    (@ [$($game:tt)*] [$($shared:tt)*]
+        $(#[$attr:meta])*
+        pub fn $name:ident(&$self:ident $(, $arg:ident: $ty:ty)*) $(-> $ret:ty)? $body:block
+        $($rest:tt)*
+    ) => {
+        game_api!(@
+            [$($game)* $(#[$attr])* pub fn $name(&$self $(, $arg: $ty)*) $(-> $ret)? $body]
+            [$($shared)* $(#[$attr])* pub fn $name($($arg: $ty),*) $(-> $ret)? {
+                DEFAULT.with(|g| g.borrow().$name($($arg),*))
+            }]
+            $($rest)*
+        );
+    };
+    ($($methods:tt)*) => {
+        game_api!(@ [] [] $($methods)*);
+    };
+}
+
+game_api! {
     /// Advance the game state based on the current timestamp (seconds)
-    pub fn tick(now: f64) {
-        GAME.with(|g| g.borrow_mut().tick(now));
+    pub fn tick(&mut self, now: f64) {
+        self.state.tick(now);
     }
 
-    /// Attempt to build a building by name
-    pub fn build(name: &str) -> bool {
-        GAME.with(|g| g.borrow_mut().build(name.into()))
+    /// Attempt to build a building by name. Returns an action outcome as a
+    /// JSON string.
+    pub fn build(&mut self, name: &str) -> String {
+        outcome_json(self.state.build(name.into()))
+    }
+
+    /// Sell up to `amount` buildings by name for a partial refund, returning
+    /// how many were sold
+    pub fn sell(&mut self, name: &str, amount: u32) -> u32 {
+        self.state.sell(name.into(), amount)
     }
 
     /// Get a resource amount by name
-    pub fn get_resource(name: &str) -> f64 {
-        GAME.with(|g| g.borrow().get_resource(name.into()))
+    pub fn get_resource(&self, name: &str) -> f64 {
+        self.state.get_resource(name.into())
     }
 
     /// Get the cost of constructing the next level of a building as a JSON string
-    pub fn building_cost(name: &str) -> String {
-        GAME.with(|g| {
-            let cost = g.borrow().build_cost(name.into());
-            serde_json::to_string(&cost).expect("serialize cost")
-        })
+    pub fn building_cost(&self, name: &str) -> String {
+        let cost = self.state.build_cost(name.into());
+        serde_json::to_string(&cost).expect("serialize cost")
+    }
+
+    /// Attempt to purchase the next level of an upgrade by name. Returns an
+    /// action outcome as a JSON string.
+    pub fn buy_upgrade(&mut self, name: &str) -> String {
+        outcome_json(self.state.buy_upgrade(name.into()))
+    }
+
+    /// Get the cost of the next level of an upgrade as a JSON string
+    pub fn upgrade_cost(&self, name: &str) -> String {
+        let cost = self.state.upgrade_cost(name.into());
+        serde_json::to_string(&cost).expect("serialize cost")
+    }
+
+    /// Per-building output and modifiers as a JSON string
+    pub fn building_yields(&self) -> String {
+        let breakdown = self.state.building_breakdown();
+        serde_json::to_string(&breakdown).expect("serialize breakdown")
+    }
+
+    /// Buildings and modifiers contributing to a resource as a JSON string
+    pub fn yield_breakdown(&self, resource: &str) -> String {
+        let breakdown = self.state.yield_breakdown_by_name(resource.into());
+        serde_json::to_string(&breakdown).expect("serialize breakdown")
+    }
+
+    /// Craft a recipe by name `times` times
+    pub fn craft(&mut self, name: &str, times: u32) -> bool {
+        self.state.craft(name.into(), times)
+    }
+
+    /// Ingredients and products of every recipe as a JSON string
+    pub fn recipes(&self) -> String {
+        let recipes = self.state.recipes();
+        serde_json::to_string(&recipes).expect("serialize recipes")
+    }
+
+    /// Everything the UI renders in a frame as a JSON string. Drains the
+    /// pending log.
+    pub fn snapshot(&mut self) -> String {
+        let snapshot = self.state.snapshot();
+        serde_json::to_string(&snapshot).expect("serialize snapshot")
+    }
+
+    /// Sections of the state that changed after `version` as a JSON
+    /// string, along with the version to ask for next
+    pub fn changes_since(&mut self, version: u32) -> String {
+        let changes = self.state.changes_since(version);
+        serde_json::to_string(&changes).expect("serialize changes")
+    }
+
+    /// Predict resources after taking the actions in `actions`, a JSON
+    /// list of queue entries, and waiting `seconds`. Returns the simulation
+    /// as a JSON string, or `null` if the actions cannot be parsed.
+    pub fn simulate(&self, actions: &str, seconds: f64) -> String {
+        let simulation = serde_json::from_str::<Vec<QueuedAction>>(actions)
+            .ok()
+            .map(|actions| self.state.simulate(&actions, seconds));
+        serde_json::to_string(&simulation).expect("serialize simulation")
+    }
+
+    /// Seconds until each building, upgrade and unresearched technology is
+    /// affordable, with the bottleneck resource, as a JSON string. Seconds
+    /// are `null` when a missing resource is not being produced.
+    pub fn time_to_afford(&self) -> String {
+        let estimates = self.state.afford_estimates();
+        serde_json::to_string(&estimates).expect("serialize estimates")
+    }
+
+    /// Current market prices as a JSON string
+    pub fn market_prices(&self) -> String {
+        let prices = self.state.market_prices();
+        serde_json::to_string(&prices).expect("serialize prices")
+    }
+
+    /// Sell a resource for gold, returning the gold received
+    pub fn market_sell(&mut self, resource: &str, amount: f64) -> f64 {
+        self.state.market_sell(resource.into(), amount)
+    }
+
+    /// Buy a resource with gold, returning the gold spent
+    pub fn market_buy(&mut self, resource: &str, amount: f64) -> f64 {
+        self.state.market_buy(resource.into(), amount)
     }
 
     /// Number of buildings already constructed of the given type
-    pub fn building_count(name: &str) -> u32 {
-        GAME.with(|g| g.borrow().building_count(name.into()))
+    pub fn building_count(&self, name: &str) -> u32 {
+        self.state.building_count(name.into())
+    }
+
+    /// Number of running buildings of the given type
+    pub fn active_count(&self, name: &str) -> u32 {
+        self.state.active_count(name.into())
+    }
+
+    /// Run `count` buildings of the given type and pause the rest
+    pub fn set_active(&mut self, name: &str, count: u32) -> u32 {
+        self.state.set_active(name.into(), count)
+    }
+
+    /// Population count, cap and worker assignments as a JSON string
+    pub fn population(&self) -> String {
+        let status = self.state.population_status();
+        serde_json::to_string(&status).expect("serialize population")
+    }
+
+    /// Staff the listed building types first, given as comma separated names
+    pub fn set_worker_priority(&mut self, names: &str) -> bool {
+        self.state.set_worker_priority(names.into())
     }
 
     /// Net resource change per second for the given resource
-    pub fn get_resource_rate(name: &str) -> f64 {
-        GAME.with(|g| g.borrow().get_resource_rate(name.into()))
+    pub fn get_resource_rate(&self, name: &str) -> f64 {
+        self.state.get_resource_rate(name.into())
+    }
+
+    /// Unmet demand per second for each resource as a JSON string
+    pub fn shortages(&self) -> String {
+        let shortages = self.state.shortages();
+        serde_json::to_string(&shortages).expect("serialize shortages")
+    }
+
+    /// Energy supply, demand, satisfaction and storage as a JSON string
+    pub fn power_status(&self) -> String {
+        let status = self.state.power_status();
+        serde_json::to_string(&status).expect("serialize power status")
     }
 
     /// Save game to a base64 string
-    pub fn save() -> String {
-        GAME.with(|g| g.borrow().save_string())
+    pub fn save(&self) -> String {
+        self.state.save_string()
     }
 
     /// Load game from a base64 string
-    pub fn load(data: &str) {
-        if let Some(state) = GameState::load_string(data) {
-            GAME.with(|g| *g.borrow_mut() = state);
+    pub fn load(&mut self, data: &str) {
+        if let Some(mut state) = GameState::load_string(data) {
+            // Keep versions increasing so UI queries see the loaded state
+            state.changes = std::mem::take(&mut self.state.changes);
+            state.changes.mark_all();
+            self.state = state;
         }
     }
 
     /// Change tick rate in seconds
-    pub fn set_tick_rate(rate: f64) {
-        GAME.with(|g| g.borrow_mut().tick_rate = rate.max(0.2).min(10.0));
+    pub fn set_tick_rate(&mut self, rate: f64) {
+        self.state.tick_rate = rate.max(0.2).min(10.0);
+    }
+
+    /// Set how many game seconds pass per real second. Returns the speed
+    /// applied after clamping.
+    pub fn set_speed(&mut self, speed: f64) -> f64 {
+        self.state.set_speed(speed)
     }
 
     /// Retrieve the next log message from the game if available
-    pub fn pop_log() -> Option<String> {
-        GAME.with(|g| g.borrow_mut().pop_log())
+    pub fn pop_log(&mut self) -> Option<String> {
+        self.state.pop_log()
     }
 
     /// Get the list of unlocked achievements as a JSON string
-    pub fn achievements() -> String {
-        GAME.with(|g| {
-            let list = g.borrow().achievements_list();
-            serde_json::to_string(&list).expect("serialize achievements")
-        })
-    }
-
-    /// Attempt to research a technology using science
-    pub fn research(name: &str) -> bool {
-        let tech = match name {
-            "mining" => Tech::Mining,
-            "baking" => Tech::Baking,
-            "electricity" => Tech::Electricity,
-            "education" => Tech::Education,
-            "alchemy" => Tech::Alchemy,
-            _ => return false,
+    pub fn achievements(&self) -> String {
+        let list = self.state.achievements_list();
+        serde_json::to_string(&list).expect("serialize achievements")
+    }
+
+    /// Get the current quest and progress of each chain as a JSON string
+    pub fn quests(&self) -> String {
+        let quests = self.state.quest_status();
+        serde_json::to_string(&quests).expect("serialize quests")
+    }
+
+    /// Get the running timed effects as a JSON string
+    pub fn active_effects(&self) -> String {
+        let effects = self.state.active_effects();
+        serde_json::to_string(&effects).expect("serialize effects")
+    }
+
+    /// Cast a spell by name, returning the outcome as a JSON string
+    pub fn cast(&mut self, spell: &str) -> String {
+        let result = self.state.cast(spell.into());
+        serde_json::to_string(&result).expect("serialize cast result")
+    }
+
+    /// Seconds until each recharging spell is ready as a JSON string
+    pub fn spell_cooldowns(&self) -> String {
+        let cooldowns = self.state.spell_cooldowns();
+        serde_json::to_string(&cooldowns).expect("serialize cooldowns")
+    }
+
+    /// Attempt to research a technology using science. Returns an action
+    /// outcome as a JSON string; an owned technology is `already_owned`.
+    pub fn research(&mut self, name: &str) -> String {
+        let tech = match name.parse::<Tech>() {
+            Ok(tech) => tech,
+            Err(err) => return outcome_json(Err(err.into())),
         };
-        GAME.with(|g| {
-            let mut state = g.borrow_mut();
-            let res_ptr = &mut state.resources as *mut _;
-            // SAFETY: no other mutable borrows exist
-            unsafe { state.research.try_unlock(tech, &mut *res_ptr) }
-        })
+        outcome_json(self.state.research(tech))
+    }
+
+    /// Let the auto-builder for a building spend up to `fraction` of each
+    /// resource per level. Returns false while automation is locked.
+    pub fn set_auto_build(&mut self, name: &str, fraction: f64) -> bool {
+        self.state.set_auto_build(name.into(), fraction)
+    }
+
+    /// Queue `count` levels of a building. A building still locked when it
+    /// reaches the front of the queue is dropped.
+    pub fn queue_build(&mut self, name: &str, count: u32) -> bool {
+        self.state.queue_build(name.into(), count)
+    }
+
+    /// Queue researching a technology
+    pub fn queue_research(&mut self, name: &str) -> bool {
+        self.state.queue_research(name.into())
+    }
+
+    /// Queue one level of an upgrade
+    pub fn queue_upgrade(&mut self, name: &str) -> bool {
+        self.state.queue_upgrade(name.into())
+    }
+
+    /// Get the purchase queue as a JSON string
+    pub fn purchase_queue(&self) -> String {
+        let queue = self.state.automation.queue();
+        serde_json::to_string(&queue).expect("serialize queue")
+    }
+
+    /// Remove every queued purchase
+    pub fn clear_queue(&mut self) {
+        self.state.automation.clear_queue();
     }
 
     /// Perform a prestige reset
-    pub fn prestige() {
-        GAME.with(|g| g.borrow_mut().prestige());
+    pub fn prestige(&mut self) {
+        self.state.prestige();
     }
 
     /// Current prestige points
-    pub fn prestige_points() -> u32 {
-        GAME.with(|g| g.borrow().prestige.points)
+    pub fn prestige_points(&self) -> u32 {
+        self.state.prestige.points
+    }
+}
+
+// Time controls and cheats from `debug.rs`
+#[cfg(feature = "debug-tools")]
+game_api! {
+    /// Run `seconds` of game time immediately
+    pub fn fast_forward(&mut self, seconds: f64) {
+        self.state.fast_forward(seconds);
+    }
+
+    /// Run exactly one tick
+    pub fn step(&mut self) {
+        self.state.step();
+    }
+
+    /// Stop or resume game time
+    pub fn set_frozen(&mut self, frozen: bool) {
+        self.state.frozen = frozen;
+    }
+
+    /// Add `amount` of a resource by name
+    pub fn grant(&mut self, name: &str, amount: f64) -> bool {
+        self.state.grant(name.into(), amount)
     }
 }
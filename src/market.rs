@@ -0,0 +1,181 @@
+use crate::resources::{ResourceKind, Resources};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Resources that can be traded for gold
+pub const TRADABLE: [ResourceKind; 4] = [
+    ResourceKind::Wood,
+    ResourceKind::Stone,
+    ResourceKind::Food,
+    ResourceKind::Iron,
+];
+/// Buying costs this much more than selling
+pub const BUY_MARKUP: f64 = 1.5;
+/// Units sold that halve the sell price
+pub const SATURATION: f64 = 500.0;
+/// Seconds for sale pressure to fall by a factor of e
+pub const PRESSURE_DECAY: f64 = 300.0;
+/// Largest relative price change per second of drift
+pub const VOLATILITY: f64 = 0.02;
+
+/// Gold paid per unit before drift and pressure
+fn base_price(kind: ResourceKind) -> f64 {
+    match kind {
+        ResourceKind::Wood | ResourceKind::Stone => 0.1,
+        ResourceKind::Food => 0.05,
+        ResourceKind::Iron => 0.5,
+        _ => 0.0,
+    }
+}
+
+/// Current prices of one resource
+#[derive(Serialize)]
+pub struct MarketPrice {
+    /// Resource traded
+    pub resource: ResourceKind,
+    /// Gold paid to buy one unit
+    pub buy: f64,
+    /// Gold received for selling one more unit
+    pub sell: f64,
+}
+
+/// Trade prices that drift over time and fall as the player sells
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct Market {
+    /// Random walk factor applied to base prices
+    drift: HashMap<ResourceKind, f64>,
+    /// Recently sold units depressing sell prices
+    pressure: HashMap<ResourceKind, f64>,
+}
+
+impl Market {
+    fn drift(&self, kind: ResourceKind) -> f64 {
+        self.drift.get(&kind).copied().unwrap_or(1.0)
+    }
+
+    fn pressure(&self, kind: ResourceKind) -> f64 {
+        self.pressure.get(&kind).copied().unwrap_or(0.0)
+    }
+
+    /// Gold paid to buy one unit
+    pub fn buy_price(&self, kind: ResourceKind) -> f64 {
+        base_price(kind) * self.drift(kind) * BUY_MARKUP
+    }
+
+    /// Gold received for selling one more unit
+    pub fn sell_price(&self, kind: ResourceKind) -> f64 {
+        base_price(kind) * self.drift(kind) / (1.0 + self.pressure(kind) / SATURATION)
+    }
+
+    /// Price table of every tradable resource
+    pub fn prices(&self) -> Vec<MarketPrice> {
+        TRADABLE
+            .iter()
+            .map(|&kind| MarketPrice {
+                resource: kind,
+                buy: self.buy_price(kind),
+                sell: self.sell_price(kind),
+            })
+            .collect()
+    }
+
+    /// Drift prices randomly towards their base and let sale pressure fade
+    pub fn update<R: Rng>(&mut self, rng: &mut R, seconds: f64) {
+        for kind in TRADABLE {
+            let drift = self.drift.entry(kind).or_insert(1.0);
+            let step = rng.random_range(-1.0..1.0) * VOLATILITY * seconds.sqrt();
+            *drift = (*drift * (1.0 + step) + (1.0 - *drift) * 0.01 * seconds).clamp(0.5, 2.0);
+        }
+        let decay = (-seconds / PRESSURE_DECAY).exp();
+        self.pressure.retain(|_, p| {
+            *p *= decay;
+            *p > 1e-3
+        });
+    }
+
+    /// Sell `amount` units for gold. Each unit sold lowers the price of the
+    /// next. Returns the gold received, or `None` if the resource cannot be
+    /// traded or there is not enough of it.
+    pub fn sell(&mut self, kind: ResourceKind, amount: f64, res: &mut Resources) -> Option<f64> {
+        if !TRADABLE.contains(&kind)
+            || !amount.is_finite()
+            || amount <= 0.0
+            || res.get(kind) < amount
+        {
+            return None;
+        }
+        let p = self.pressure(kind);
+        // Integral of the sell price over the units sold
+        let gold = base_price(kind)
+            * self.drift(kind)
+            * SATURATION
+            * ((SATURATION + p + amount) / (SATURATION + p)).ln();
+        *res.get_mut(kind) -= amount;
+        res.gold += gold;
+        self.pressure.insert(kind, p + amount);
+        Some(gold)
+    }
+
+    /// Buy `amount` units with gold. Returns the gold spent, or `None` if
+    /// the resource cannot be traded or there is not enough gold.
+    pub fn buy(&mut self, kind: ResourceKind, amount: f64, res: &mut Resources) -> Option<f64> {
+        let cost = self.buy_price(kind) * amount;
+        if !TRADABLE.contains(&kind) || !amount.is_finite() || amount <= 0.0 || res.gold < cost {
+            return None;
+        }
+        res.gold -= cost;
+        *res.get_mut(kind) += amount;
+        Some(cost)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::res;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn selling_has_diminishing_returns() {
+        let mut m = Market::default();
+        let mut r = res(1000.0, 0.0, 0.0, 0.0, 0.0);
+        let first = m.sell(ResourceKind::Wood, 500.0, &mut r).unwrap();
+        let second = m.sell(ResourceKind::Wood, 500.0, &mut r).unwrap();
+        assert!(first < 500.0 * 0.1);
+        assert!(second < first);
+        assert!((m.sell_price(ResourceKind::Wood) - 0.1 / 3.0).abs() < 1e-9);
+        assert!(m.sell(ResourceKind::Wood, 1.0, &mut r).is_none());
+        assert!(m.sell(ResourceKind::Mana, 1.0, &mut r).is_none());
+        assert!(m.sell(ResourceKind::Wood, f64::NAN, &mut r).is_none());
+        m.update(&mut StdRng::seed_from_u64(0), 3000.0);
+        assert!(m.sell_price(ResourceKind::Wood) > 0.05);
+    }
+
+    #[wasm_bindgen_test]
+    fn buying_costs_gold() {
+        let mut m = Market::default();
+        let mut r = res(0.0, 0.0, 0.0, 0.0, 10.0);
+        assert!(m.buy(ResourceKind::Iron, 20.0, &mut r).is_none());
+        assert!(m.buy(ResourceKind::Iron, f64::NAN, &mut r).is_none());
+        assert!(m.buy(ResourceKind::Iron, f64::INFINITY, &mut r).is_none());
+        assert_eq!(m.buy(ResourceKind::Iron, 10.0, &mut r), Some(7.5));
+        assert_eq!(r.iron, 10.0);
+        assert!((r.gold - 2.5).abs() < 1e-9);
+    }
+
+    #[wasm_bindgen_test]
+    fn drift_stays_bounded() {
+        let mut m = Market::default();
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..10_000 {
+            m.update(&mut rng, 1.0);
+        }
+        for p in m.prices() {
+            let base = base_price(p.resource) * BUY_MARKUP;
+            assert!(p.buy >= base * 0.5 && p.buy <= base * 2.0);
+        }
+    }
+}
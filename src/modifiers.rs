@@ -0,0 +1,110 @@
+use crate::buildings::BuildingType;
+use crate::resources::{ResourceKind, Resources};
+use serde::Serialize;
+
+/// How a modifier changes output
+#[derive(Clone, Copy, Debug, Serialize, PartialEq)]
+#[serde(tag = "op", content = "value")]
+pub enum ModifierOp {
+    /// Summed with other additive modifiers, e.g. 0.5 for +50%
+    Additive(f64),
+    /// Multiplied with the result of the additive modifiers
+    Multiplicative(f64),
+}
+
+/// A single change to building output and where it comes from
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct Modifier {
+    /// Human readable origin, e.g. "Efficiency upgrade"
+    pub source: String,
+    /// Building affected, or every building when `None`
+    pub building: Option<BuildingType>,
+    /// Resource affected, or every resource when `None`
+    pub resource: Option<ResourceKind>,
+    /// Effect on output
+    pub op: ModifierOp,
+}
+
+impl Modifier {
+    /// Modifier applying to every building and resource
+    pub fn global(source: impl Into<String>, op: ModifierOp) -> Self {
+        Modifier {
+            source: source.into(),
+            building: None,
+            resource: None,
+            op,
+        }
+    }
+
+    /// Modifier applying to every resource produced by one building type
+    pub fn building(source: impl Into<String>, building: BuildingType, op: ModifierOp) -> Self {
+        Modifier {
+            building: Some(building),
+            ..Self::global(source, op)
+        }
+    }
+
+    /// Modifier applying to one resource from every building
+    pub fn resource(source: impl Into<String>, resource: ResourceKind, op: ModifierOp) -> Self {
+        Modifier {
+            resource: Some(resource),
+            ..Self::global(source, op)
+        }
+    }
+
+    /// Whether the modifier affects `resource` produced by `building`
+    pub fn applies(&self, building: BuildingType, resource: ResourceKind) -> bool {
+        self.building.is_none_or(|b| b == building) && self.resource.is_none_or(|r| r == resource)
+    }
+}
+
+/// Combined multiplier of the modifiers affecting `resource` from `building`.
+/// Additive modifiers are summed first, then multiplicative ones applied.
+pub fn multiplier(mods: &[Modifier], building: BuildingType, resource: ResourceKind) -> f64 {
+    let (add, mul) =
+        mods.iter()
+            .filter(|m| m.applies(building, resource))
+            .fold((1.0, 1.0), |(add, mul), m| match m.op {
+                ModifierOp::Additive(v) => (add + v, mul),
+                ModifierOp::Multiplicative(v) => (add, mul * v),
+            });
+    add * mul
+}
+
+/// Apply modifiers to a building's yield. Inputs are scaled along with
+/// outputs, so a boosted building also consumes more.
+pub fn apply(mods: &[Modifier], building: BuildingType, base: &Resources) -> Resources {
+    let mut out = *base;
+    for kind in ResourceKind::ALL {
+        *out.get_mut(kind) *= multiplier(mods, building, kind);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn additive_before_multiplicative() {
+        let mods = [
+            Modifier::global("a", ModifierOp::Additive(0.5)),
+            Modifier::global("b", ModifierOp::Additive(0.5)),
+            Modifier::resource("c", ResourceKind::Food, ModifierOp::Multiplicative(3.0)),
+            Modifier::building("d", BuildingType::Quarry, ModifierOp::Multiplicative(10.0)),
+        ];
+        assert_eq!(
+            multiplier(&mods, BuildingType::Farm, ResourceKind::Food),
+            6.0
+        );
+        assert_eq!(
+            multiplier(&mods, BuildingType::Farm, ResourceKind::Wood),
+            2.0
+        );
+        let bakery = crate::resources::res(0.0, 0.0, -1.0, 0.0, 0.5);
+        let out = apply(&mods, BuildingType::Bakery, &bakery);
+        assert_eq!(out.food, -6.0);
+        assert_eq!(out.gold, 1.0);
+    }
+}
@@ -0,0 +1,173 @@
+use crate::buildings::{BuildingType, Buildings};
+use crate::resources::Resources;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Population supported without any houses
+pub const BASE_POPULATION_CAP: u32 = 5;
+/// Extra population supported by each house
+pub const HOUSE_CAPACITY: u32 = 4;
+/// Food eaten to raise one new worker
+pub const FOOD_PER_WORKER: f64 = 10.0;
+/// Food each worker eats per second
+pub const FOOD_UPKEEP_PER_WORKER: f64 = 0.1;
+/// Seconds without enough food before a worker leaves
+pub const STARVATION_SECONDS: f64 = 10.0;
+/// Workers that never leave, so a farm can always be staffed
+pub const MIN_POPULATION: u32 = 1;
+
+/// Workers available to staff buildings
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Population {
+    /// Number of workers
+    pub count: u32,
+    /// Building types staffed before all others, in order
+    pub priority: Vec<BuildingType>,
+    /// Seconds saved up towards the next worker, at most one
+    #[serde(default)]
+    pub growth: f64,
+    /// Seconds the workforce has gone without enough food
+    #[serde(default)]
+    pub hunger: f64,
+    /// Housing granted to saves from before population existed
+    #[serde(default)]
+    pub legacy_housing: u32,
+}
+
+impl Default for Population {
+    fn default() -> Self {
+        Population {
+            count: BASE_POPULATION_CAP,
+            priority: Vec::new(),
+            growth: 0.0,
+            hunger: 0.0,
+            legacy_housing: 0,
+        }
+    }
+}
+
+/// Summary of the workforce for the UI
+#[derive(Serialize)]
+pub struct PopulationStatus {
+    /// Number of workers
+    pub count: u32,
+    /// Maximum population supported by houses
+    pub cap: u32,
+    /// Workers without a building to run
+    pub idle: u32,
+    /// Workers assigned to each building type
+    pub assigned: HashMap<BuildingType, u32>,
+}
+
+impl Population {
+    /// Maximum population supported by houses
+    pub fn cap(&self, buildings: &Buildings) -> u32 {
+        BASE_POPULATION_CAP
+            + buildings.level(BuildingType::House) * HOUSE_CAPACITY
+            + self.legacy_housing
+    }
+
+    /// Food the workforce eats per second
+    pub fn upkeep(&self) -> Resources {
+        Resources {
+            food: self.count as f64 * FOOD_UPKEEP_PER_WORKER,
+            ..Resources::default()
+        }
+    }
+
+    /// Track how long the workforce has gone hungry. One worker leaves per
+    /// `STARVATION_SECONDS` without enough food, down to `MIN_POPULATION`.
+    /// Returns true when one left.
+    pub fn starve(&mut self, fed: bool, seconds: f64) -> bool {
+        if fed || self.count <= MIN_POPULATION {
+            self.hunger = 0.0;
+            return false;
+        }
+        self.hunger += seconds;
+        // Tolerate rounding so short ticks summing to the limit count
+        if self.hunger < STARVATION_SECONDS - 1e-9 {
+            return false;
+        }
+        self.hunger = (self.hunger - STARVATION_SECONDS).max(0.0);
+        self.count -= 1;
+        true
+    }
+
+    /// Give a save from before population existed enough workers, and
+    /// housing for them, to staff every building it owns
+    pub fn settle(&mut self, buildings: &Buildings) {
+        let needed: u32 = BuildingType::ALL
+            .iter()
+            .map(|&ty| buildings.level(ty) * Buildings::workers_per_level(ty))
+            .sum();
+        self.legacy_housing = needed.saturating_sub(self.cap(buildings));
+        self.count = self.count.max(needed);
+    }
+
+    /// Raise up to one new worker per second of `seconds` from spare food
+    /// if there is housing for them. Workers without housing leave. Returns
+    /// true when a worker joined.
+    pub fn grow(&mut self, buildings: &Buildings, res: &mut Resources, seconds: f64) -> bool {
+        let cap = self.cap(buildings);
+        if self.count > cap {
+            self.count = cap;
+        }
+        self.growth += seconds;
+        let mut grew = false;
+        while self.growth >= 1.0 && self.count < cap && res.food >= FOOD_PER_WORKER {
+            res.food -= FOOD_PER_WORKER;
+            self.count += 1;
+            self.growth -= 1.0;
+            grew = true;
+        }
+        self.growth = self.growth.min(1.0);
+        grew
+    }
+
+    /// Building types in the order workers are assigned to them
+    fn order(&self) -> Vec<BuildingType> {
+        let mut order = self.priority.clone();
+        order.extend(
+            BuildingType::ALL
+                .iter()
+                .filter(|ty| !self.priority.contains(ty)),
+        );
+        order
+    }
+
+    /// Number of levels of each building type that have enough workers to
+    /// run. Whole levels are staffed in priority order until workers run out.
+    pub fn staffed(&self, buildings: &Buildings) -> Vec<(BuildingType, u32)> {
+        let mut free = self.count;
+        let mut staffed = Vec::new();
+        for ty in self.order() {
+            let active = buildings.active(ty);
+            if active == 0 {
+                continue;
+            }
+            let per_level = Buildings::workers_per_level(ty);
+            let levels = free
+                .checked_div(per_level)
+                .map_or(active, |n| active.min(n));
+            free -= levels * per_level;
+            staffed.push((ty, levels));
+        }
+        staffed
+    }
+
+    /// Current workforce summary
+    pub fn status(&self, buildings: &Buildings) -> PopulationStatus {
+        let assigned: HashMap<_, _> = self
+            .staffed(buildings)
+            .into_iter()
+            .map(|(ty, levels)| (ty, levels * Buildings::workers_per_level(ty)))
+            .filter(|&(_, workers)| workers > 0)
+            .collect();
+        PopulationStatus {
+            count: self.count,
+            cap: self.cap(buildings),
+            idle: self.count - assigned.values().sum::<u32>(),
+            assigned,
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use crate::buildings::{BuildingType, Buildings};
+use crate::production::Production;
+use crate::resources::Resources;
+use serde::Serialize;
+
+/// Energy that can be stored without batteries
+pub const BASE_ENERGY_STORAGE: f64 = 10.0;
+/// Extra energy stored by each battery
+pub const BATTERY_CAPACITY: f64 = 50.0;
+
+/// Most energy that can be held in storage
+pub fn storage_capacity(buildings: &Buildings) -> f64 {
+    BASE_ENERGY_STORAGE + buildings.level(BuildingType::Battery) as f64 * BATTERY_CAPACITY
+}
+
+/// State of the electric grid
+#[derive(Serialize, Debug)]
+pub struct PowerStatus {
+    /// Energy generated per second
+    pub supply: f64,
+    /// Energy powered buildings need per second to run fully
+    pub demand: f64,
+    /// Fraction of demand met by generation and storage, from 0 to 1
+    pub satisfaction: f64,
+    /// Energy held in storage
+    pub stored: f64,
+    /// Most energy storage can hold
+    pub capacity: f64,
+}
+
+/// Summarise the grid from building yields per second and the utilization
+/// production reached over one tick. Satisfaction is the share of demand
+/// that was actually powered, so it matches how fully buildings ran.
+pub fn status(
+    yields: &[(BuildingType, Resources)],
+    production: &Production,
+    stored: f64,
+    capacity: f64,
+) -> PowerStatus {
+    let mut supply = 0.0;
+    let mut demand = 0.0;
+    let mut powered = 0.0;
+    for (ty, y) in yields {
+        let utilization = production.utilization.get(ty).copied().unwrap_or(0.0);
+        if y.energy > 0.0 {
+            supply += y.energy * utilization;
+        } else {
+            demand -= y.energy;
+            powered -= y.energy * utilization;
+        }
+    }
+    let satisfaction = if demand > 0.0 { powered / demand } else { 1.0 };
+    PowerStatus {
+        supply,
+        demand,
+        satisfaction,
+        stored,
+        capacity,
+    }
+}
@@ -1,7 +1,12 @@
+use crate::modifiers::{Modifier, ModifierOp};
+use crate::resources::Resources;
 use serde::{Deserialize, Serialize};
 
+/// Gold each minted coin is worth towards prestige
+pub const COIN_PRESTIGE_VALUE: f64 = 1000.0;
+
 /// Persistent prestige data
-#[derive(Default, Serialize, Deserialize)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct Prestige {
     /// Points earned from resets
     pub points: u32,
@@ -12,4 +17,20 @@ impl Prestige {
     pub fn bonus_multiplier(&self) -> f64 {
         1.0 + self.points as f64 * 0.05
     }
+
+    /// Points a reset would earn with the given resources
+    pub fn points_for(res: &Resources) -> u32 {
+        ((res.gold + res.coins * COIN_PRESTIGE_VALUE) / 1e6)
+            .sqrt()
+            .floor() as u32
+    }
+
+    /// Output modifiers granted by prestige points
+    pub fn modifiers(&self) -> Vec<Modifier> {
+        if self.points == 0 {
+            return Vec::new();
+        }
+        let bonus = self.bonus_multiplier() - 1.0;
+        vec![Modifier::global("Prestige", ModifierOp::Additive(bonus))]
+    }
 }
@@ -0,0 +1,149 @@
+use crate::buildings::BuildingType;
+use crate::resources::Resources;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Result of running every building for one tick
+#[derive(Default, Clone, Serialize, Debug)]
+pub struct Production {
+    /// Net change to the stockpile
+    pub net: Resources,
+    /// Demand that could not be met for each resource
+    pub shortages: Resources,
+    /// Fraction of full output each building type ran at
+    pub utilization: HashMap<BuildingType, f64>,
+    /// Fraction of the upkeep that was paid
+    pub upkeep_met: f64,
+}
+
+/// Amounts of each resource a yield consumes, as positive numbers
+fn inputs(y: &Resources) -> Resources {
+    y.map(|v| (-v).max(0.0))
+}
+
+/// Run building yields against the stockpile. Buildings that only produce
+/// always run at full output. Consumers share each input in proportion to
+/// their demand and run at the fraction allowed by their scarcest input,
+/// producing proportionally less. `upkeep` is demand that produces nothing,
+/// such as food eaten by workers, and is rationed like any other consumer.
+///
+/// Outputs of consumers can feed other consumers in the same tick, e.g. a
+/// generator paying upkeep powering a lab. This is found by repeating the
+/// rationing with the outputs of the previous round added to supply. Each
+/// round only raises fractions, so inputs are never overdrawn.
+pub fn solve(
+    stock: &Resources,
+    yields: &[(BuildingType, Resources)],
+    upkeep: &Resources,
+) -> Production {
+    let consumers: Vec<Resources> = yields
+        .iter()
+        .map(|(_, y)| *y)
+        .chain(std::iter::once(upkeep.scale(-1.0)))
+        .collect();
+    let mut demand = Resources::default();
+    for y in &consumers {
+        demand.add(&inputs(y));
+    }
+    let mut fractions: Vec<f64> = consumers
+        .iter()
+        .map(|y| {
+            if inputs(y).values().iter().all(|&v| v == 0.0) {
+                1.0
+            } else {
+                0.0
+            }
+        })
+        .collect();
+    let mut supply = *stock;
+    for _ in 0..=consumers.len() {
+        supply = *stock;
+        for (y, &f) in consumers.iter().zip(&fractions) {
+            supply.add(&y.map(|v| v.max(0.0)).scale(f));
+        }
+        let share = supply.combine(&demand, |s, d| if d > s { s / d } else { 1.0 });
+        let next: Vec<f64> = consumers
+            .iter()
+            .map(|y| {
+                inputs(y)
+                    .values()
+                    .iter()
+                    .zip(share.values())
+                    .filter(|(&needed, _)| needed > 0.0)
+                    .fold(1.0, |f, (_, s)| f64::min(f, s))
+            })
+            .collect();
+        if next == fractions {
+            break;
+        }
+        fractions = next;
+    }
+
+    let mut production = Production {
+        shortages: demand.combine(&supply, |d, s| (d - s).max(0.0)),
+        ..Production::default()
+    };
+    for (y, &fraction) in consumers.iter().zip(&fractions) {
+        production.net.add(&y.scale(fraction));
+    }
+    for (&(ty, _), &fraction) in yields.iter().zip(&fractions) {
+        production.utilization.insert(ty, fraction);
+    }
+    production.upkeep_met = fractions[yields.len()];
+    production
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::{res, res_ext};
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn consumers_share_scarce_input() {
+        let stock = res(0.0, 0.0, 1.0, 0.0, 0.0);
+        let yields = [
+            (BuildingType::Farm, res(0.0, 0.0, 1.0, 0.0, 0.0)),
+            (BuildingType::Bakery, res(0.0, 0.0, -4.0, 0.0, 0.8)),
+        ];
+        let p = solve(&stock, &yields, &Resources::default());
+        assert_eq!(p.utilization[&BuildingType::Farm], 1.0);
+        assert_eq!(p.utilization[&BuildingType::Bakery], 0.5);
+        assert!((p.net.food + 1.0).abs() < 1e-9);
+        assert!((p.net.gold - 0.4).abs() < 1e-9);
+        assert!((p.shortages.food - 2.0).abs() < 1e-9);
+    }
+
+    #[wasm_bindgen_test]
+    fn consumer_output_feeds_consumer() {
+        let stock = res(0.0, 0.0, 0.0, 1.0, 0.0);
+        let generator = res_ext(0.0, 0.0, 0.0, -0.1, 0.0, 1.0, 0.0, 0.0);
+        let lab = res_ext(0.0, 0.0, 0.0, 0.0, 0.0, -0.5, 0.5, 0.0);
+        let p = solve(
+            &stock,
+            &[
+                (BuildingType::Generator, generator),
+                (BuildingType::Lab, lab),
+            ],
+            &Resources::default(),
+        );
+        assert_eq!(p.utilization[&BuildingType::Generator], 1.0);
+        assert_eq!(p.utilization[&BuildingType::Lab], 1.0);
+        assert!((p.net.energy - 0.5).abs() < 1e-9);
+        assert_eq!(p.shortages.energy, 0.0);
+    }
+
+    #[wasm_bindgen_test]
+    fn upkeep_shares_with_consumers() {
+        let yields = [
+            (BuildingType::Farm, res(0.0, 0.0, 1.0, 0.0, 0.0)),
+            (BuildingType::Bakery, res(0.0, 0.0, -1.0, 0.0, 0.2)),
+        ];
+        let upkeep = res(0.0, 0.0, 1.0, 0.0, 0.0);
+        let p = solve(&Resources::default(), &yields, &upkeep);
+        assert_eq!(p.utilization[&BuildingType::Bakery], 0.5);
+        assert_eq!(p.upkeep_met, 0.5);
+        assert!(p.net.food.abs() < 1e-9);
+        assert!((p.shortages.food - 1.0).abs() < 1e-9);
+    }
+}
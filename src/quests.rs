@@ -0,0 +1,160 @@
+use crate::buildings::{BuildingType, Buildings};
+use crate::research::{Research, Tech};
+use crate::resources::{ResourceKind, Resources};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Condition a quest waits for
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind")]
+pub enum Objective {
+    /// Own at least `count` levels of a building
+    Build {
+        /// Building to own
+        building: BuildingType,
+        /// Levels required
+        count: u32,
+    },
+    /// Hold at least `amount` of a resource
+    Stockpile {
+        /// Resource to collect
+        resource: ResourceKind,
+        /// Amount required
+        amount: f64,
+    },
+    /// Research a technology
+    Research {
+        /// Technology to unlock
+        tech: Tech,
+    },
+}
+
+/// One step of a quest chain
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Quest {
+    /// Shown to the player
+    pub title: String,
+    /// Completion condition
+    pub objective: Objective,
+    /// Granted on completion
+    pub reward: Resources,
+}
+
+/// Quests completed one after another
+#[derive(Deserialize)]
+pub struct QuestChain {
+    /// Identifier used to track progress in saves
+    pub id: String,
+    /// Steps in order
+    pub quests: Vec<Quest>,
+}
+
+/// Every quest chain in the game, as defined in `data/quests.json`
+pub fn chains() -> &'static [QuestChain] {
+    static CHAINS: OnceLock<Vec<QuestChain>> = OnceLock::new();
+    CHAINS.get_or_init(|| {
+        serde_json::from_str(include_str!("../data/quests.json")).expect("valid quest data")
+    })
+}
+
+impl Objective {
+    /// Fraction of the objective met, from 0 to 1
+    pub fn progress(&self, buildings: &Buildings, research: &Research, res: &Resources) -> f64 {
+        let fraction = match *self {
+            Objective::Build { building, count } => buildings.level(building) as f64 / count as f64,
+            Objective::Stockpile { resource, amount } => res.get(resource) / amount,
+            Objective::Research { tech } => research.is_unlocked(tech) as u32 as f64,
+        };
+        fraction.min(1.0)
+    }
+}
+
+/// Current quest of an unfinished chain
+#[derive(Serialize)]
+pub struct QuestStatus {
+    /// Chain identifier
+    pub chain: &'static str,
+    /// Index of the current quest
+    pub step: usize,
+    /// Number of quests in the chain
+    pub steps: usize,
+    /// Current quest
+    pub quest: &'static Quest,
+    /// Fraction of the objective met
+    pub progress: f64,
+}
+
+/// Player progress through the quest chains
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct Quests {
+    /// Index of the current quest in each chain
+    progress: HashMap<String, usize>,
+}
+
+impl Quests {
+    fn step(&self, chain: &QuestChain) -> usize {
+        self.progress.get(&chain.id).copied().unwrap_or(0)
+    }
+
+    /// Complete every quest whose objective is met, granting its reward.
+    /// Returns messages for completed quests.
+    pub fn check(
+        &mut self,
+        buildings: &Buildings,
+        research: &Research,
+        res: &mut Resources,
+    ) -> Vec<String> {
+        let mut msgs = Vec::new();
+        for chain in chains() {
+            let mut step = self.step(chain);
+            while let Some(quest) = chain.quests.get(step) {
+                if quest.objective.progress(buildings, research, res) < 1.0 {
+                    break;
+                }
+                res.add(&quest.reward);
+                msgs.push(format!("Quest complete: {}", quest.title));
+                step += 1;
+            }
+            self.progress.insert(chain.id.clone(), step);
+        }
+        msgs
+    }
+
+    /// Current quest of each unfinished chain
+    pub fn status(
+        &self,
+        buildings: &Buildings,
+        research: &Research,
+        res: &Resources,
+    ) -> Vec<QuestStatus> {
+        chains()
+            .iter()
+            .filter_map(|chain| {
+                let step = self.step(chain);
+                let quest = chain.quests.get(step)?;
+                Some(QuestStatus {
+                    chain: &chain.id,
+                    step,
+                    steps: chain.quests.len(),
+                    progress: quest.objective.progress(buildings, research, res),
+                    quest,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn quest_data_loads() {
+        let ids: HashSet<_> = chains().iter().map(|chain| chain.id.as_str()).collect();
+        assert_eq!(ids.len(), chains().len());
+        assert!(chains().iter().all(|chain| !chain.quests.is_empty()));
+    }
+}
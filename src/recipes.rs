@@ -0,0 +1,139 @@
+use crate::catalogue::{self, Catalogue, UnknownId};
+use crate::resources::{res, Resources};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Recipes converting resources into other resources
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Recipe {
+    /// Wood and iron into tools
+    #[serde(alias = "Tools")]
+    Tools,
+    /// Stone and iron into steel
+    #[serde(alias = "Steel")]
+    Steel,
+    /// Gold into science at a fixed rate
+    #[serde(alias = "Scholarship")]
+    Scholarship,
+}
+
+impl Recipe {
+    /// Every recipe in display order
+    pub const ALL: [Recipe; 3] = [Recipe::Tools, Recipe::Steel, Recipe::Scholarship];
+}
+
+impl Catalogue for Recipe {
+    const ENTRIES: &'static [Self] = &Self::ALL;
+
+    fn id(self) -> &'static str {
+        match self {
+            Recipe::Tools => "tools",
+            Recipe::Steel => "steel",
+            Recipe::Scholarship => "scholarship",
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Recipe::Tools => "Tools",
+            Recipe::Steel => "Steel",
+            Recipe::Scholarship => "Scholarship",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Recipe::Tools => "Forges wood and iron into tools",
+            Recipe::Steel => "Smelts stone and iron into steel",
+            Recipe::Scholarship => "Funds research with gold",
+        }
+    }
+}
+
+impl FromStr for Recipe {
+    type Err = UnknownId;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        catalogue::parse(s)
+    }
+}
+
+impl fmt::Display for Recipe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+/// Ingredients and products of one craft
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct RecipeInfo {
+    /// Recipe described
+    pub recipe: Recipe,
+    /// Consumed by one craft
+    pub input: Resources,
+    /// Produced by one craft
+    pub output: Resources,
+}
+
+/// Static data for a recipe
+pub fn info(recipe: Recipe) -> RecipeInfo {
+    let (input, output) = match recipe {
+        Recipe::Tools => (
+            res(5.0, 0.0, 0.0, 2.0, 0.0),
+            Resources {
+                tools: 1.0,
+                ..Resources::default()
+            },
+        ),
+        Recipe::Steel => (
+            res(0.0, 5.0, 0.0, 3.0, 0.0),
+            Resources {
+                steel: 1.0,
+                ..Resources::default()
+            },
+        ),
+        Recipe::Scholarship => (
+            res(0.0, 0.0, 0.0, 0.0, 10.0),
+            Resources {
+                science: 5.0,
+                ..Resources::default()
+            },
+        ),
+    };
+    RecipeInfo {
+        recipe,
+        input,
+        output,
+    }
+}
+
+/// Craft a recipe `times` times if all ingredients are available
+pub fn craft(recipe: Recipe, times: u32, res: &mut Resources) -> bool {
+    let i = info(recipe);
+    if times > 0 && res.subtract(&i.input.scale(times as f64)) {
+        res.add(&i.output.scale(times as f64));
+        true
+    } else {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn craft_needs_all_ingredients() {
+        let mut r = res(10.0, 0.0, 0.0, 3.0, 0.0);
+        assert!(!craft(Recipe::Tools, 2, &mut r));
+        assert_eq!(r.wood, 10.0);
+        assert!(craft(Recipe::Tools, 1, &mut r));
+        assert_eq!(r.tools, 1.0);
+        assert_eq!(r.wood, 5.0);
+        assert_eq!(r.iron, 1.0);
+        assert!(!craft(Recipe::Steel, 1, &mut r));
+    }
+}
@@ -1,23 +1,132 @@
+use crate::catalogue::{self, Catalogue, UnknownId};
+use crate::error::ActionError;
+use crate::resources::Resources;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+/// Science spent on each technology
+pub const RESEARCH_COST: f64 = 100.0;
 
 /// Technologies that can be researched
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
 pub enum Tech {
     /// Unlocks mines
+    #[serde(alias = "Mining")]
     Mining,
     /// Unlocks bakeries
+    #[serde(alias = "Baking")]
     Baking,
     /// Allows construction of generators
+    #[serde(alias = "Electricity")]
     Electricity,
     /// Allows laboratories for science
+    #[serde(alias = "Education")]
     Education,
     /// Enables shrines and mana
+    #[serde(alias = "Alchemy")]
     Alchemy,
+    /// Enables auto-builders and the purchase queue
+    #[serde(alias = "Automation")]
+    Automation,
+    /// Allows smelters for iron
+    #[serde(alias = "Metallurgy")]
+    Metallurgy,
+    /// Allows forges for tools
+    #[serde(alias = "Smithing")]
+    Smithing,
+    /// Allows mints for coins
+    #[serde(alias = "Minting")]
+    Minting,
+}
+
+impl Tech {
+    /// Every technology in display order
+    pub const ALL: [Tech; 9] = [
+        Tech::Mining,
+        Tech::Baking,
+        Tech::Electricity,
+        Tech::Education,
+        Tech::Alchemy,
+        Tech::Automation,
+        Tech::Metallurgy,
+        Tech::Smithing,
+        Tech::Minting,
+    ];
+
+    /// Resources spent to research this technology
+    pub fn cost(self) -> Resources {
+        Resources {
+            science: RESEARCH_COST,
+            ..Resources::default()
+        }
+    }
+}
+
+impl Catalogue for Tech {
+    const ENTRIES: &'static [Self] = &Self::ALL;
+
+    fn id(self) -> &'static str {
+        match self {
+            Tech::Mining => "mining",
+            Tech::Baking => "baking",
+            Tech::Electricity => "electricity",
+            Tech::Education => "education",
+            Tech::Alchemy => "alchemy",
+            Tech::Automation => "automation",
+            Tech::Metallurgy => "metallurgy",
+            Tech::Smithing => "smithing",
+            Tech::Minting => "minting",
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Tech::Mining => "Mining",
+            Tech::Baking => "Baking",
+            Tech::Electricity => "Electricity",
+            Tech::Education => "Education",
+            Tech::Alchemy => "Alchemy",
+            Tech::Automation => "Automation",
+            Tech::Metallurgy => "Metallurgy",
+            Tech::Smithing => "Smithing",
+            Tech::Minting => "Minting",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Tech::Mining => "Unlocks mines",
+            Tech::Baking => "Unlocks bakeries",
+            Tech::Electricity => "Unlocks generators and batteries",
+            Tech::Education => "Unlocks labs",
+            Tech::Alchemy => "Unlocks shrines and mana",
+            Tech::Automation => "Unlocks auto-builders and the purchase queue",
+            Tech::Metallurgy => "Unlocks smelters",
+            Tech::Smithing => "Unlocks forges",
+            Tech::Minting => "Unlocks mints",
+        }
+    }
+}
+
+impl FromStr for Tech {
+    type Err = UnknownId;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        catalogue::parse(s)
+    }
+}
+
+impl fmt::Display for Tech {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
 }
 
 /// Player research tree
-#[derive(Default, Serialize, Deserialize)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct Research {
     unlocked: HashSet<Tech>,
 }
@@ -33,18 +142,19 @@ impl Research {
         self.unlocked.insert(tech);
     }
 
-    /// Attempt to unlock a technology consuming science
-    pub fn try_unlock(&mut self, tech: Tech, res: &mut crate::resources::Resources) -> bool {
+    /// Attempt to unlock a technology consuming science. Fails with
+    /// `AlreadyOwned` and spends nothing if it is already unlocked.
+    pub fn try_unlock(&mut self, tech: Tech, res: &mut Resources) -> Result<(), ActionError> {
         if self.is_unlocked(tech) {
-            return true;
+            return Err(ActionError::AlreadyOwned);
         }
-        let cost = 100.0;
-        if res.science >= cost {
-            res.science -= cost;
-            self.unlocked.insert(tech);
-            true
-        } else {
-            false
+        let cost = tech.cost();
+        if !res.subtract(&cost) {
+            return Err(ActionError::Insufficient {
+                missing: res.shortfall(&cost),
+            });
         }
+        self.unlocked.insert(tech);
+        Ok(())
     }
 }
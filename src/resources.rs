@@ -1,7 +1,11 @@
+use crate::catalogue::{self, Catalogue, UnknownId};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
 
 /// Collection of all game resources
-#[derive(Default, Clone, Copy, Serialize, Deserialize, Debug)]
+#[derive(Default, Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(default)]
 pub struct Resources {
     /// Amount of wood
     pub wood: f64,
@@ -19,9 +23,171 @@ pub struct Resources {
     pub science: f64,
     /// Amount of mana
     pub mana: f64,
+    /// Amount of crafted tools
+    pub tools: f64,
+    /// Amount of crafted steel
+    pub steel: f64,
+    /// Amount of minted coins
+    pub coins: f64,
+}
+
+/// Identifies a single resource
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceKind {
+    /// Wood
+    #[serde(alias = "Wood")]
+    Wood,
+    /// Stone
+    #[serde(alias = "Stone")]
+    Stone,
+    /// Food
+    #[serde(alias = "Food")]
+    Food,
+    /// Iron
+    #[serde(alias = "Iron")]
+    Iron,
+    /// Gold
+    #[serde(alias = "Gold")]
+    Gold,
+    /// Energy
+    #[serde(alias = "Energy")]
+    Energy,
+    /// Science
+    #[serde(alias = "Science")]
+    Science,
+    /// Mana
+    #[serde(alias = "Mana")]
+    Mana,
+    /// Tools
+    #[serde(alias = "Tools")]
+    Tools,
+    /// Steel
+    #[serde(alias = "Steel")]
+    Steel,
+    /// Coins
+    #[serde(alias = "Coins")]
+    Coins,
+}
+
+impl ResourceKind {
+    /// Every resource in field order
+    pub const ALL: [ResourceKind; 11] = [
+        ResourceKind::Wood,
+        ResourceKind::Stone,
+        ResourceKind::Food,
+        ResourceKind::Iron,
+        ResourceKind::Gold,
+        ResourceKind::Energy,
+        ResourceKind::Science,
+        ResourceKind::Mana,
+        ResourceKind::Tools,
+        ResourceKind::Steel,
+        ResourceKind::Coins,
+    ];
+}
+
+impl Catalogue for ResourceKind {
+    const ENTRIES: &'static [Self] = &Self::ALL;
+
+    fn id(self) -> &'static str {
+        match self {
+            ResourceKind::Wood => "wood",
+            ResourceKind::Stone => "stone",
+            ResourceKind::Food => "food",
+            ResourceKind::Iron => "iron",
+            ResourceKind::Gold => "gold",
+            ResourceKind::Energy => "energy",
+            ResourceKind::Science => "science",
+            ResourceKind::Mana => "mana",
+            ResourceKind::Tools => "tools",
+            ResourceKind::Steel => "steel",
+            ResourceKind::Coins => "coins",
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            ResourceKind::Wood => "Wood",
+            ResourceKind::Stone => "Stone",
+            ResourceKind::Food => "Food",
+            ResourceKind::Iron => "Iron",
+            ResourceKind::Gold => "Gold",
+            ResourceKind::Energy => "Energy",
+            ResourceKind::Science => "Science",
+            ResourceKind::Mana => "Mana",
+            ResourceKind::Tools => "Tools",
+            ResourceKind::Steel => "Steel",
+            ResourceKind::Coins => "Coins",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            ResourceKind::Wood => "Building material from lumber mills",
+            ResourceKind::Stone => "Building material from quarries",
+            ResourceKind::Food => "Feeds workers and bakeries",
+            ResourceKind::Iron => "Metal for tools, steel and generators",
+            ResourceKind::Gold => "Currency for trade and upgrades",
+            ResourceKind::Energy => "Powers labs, smelters and mints",
+            ResourceKind::Science => "Spent on research",
+            ResourceKind::Mana => "Spent on spells",
+            ResourceKind::Tools => "Crafted or forged from iron",
+            ResourceKind::Steel => "Crafted from stone and iron",
+            ResourceKind::Coins => "Minted from gold, worth extra prestige",
+        }
+    }
+}
+
+impl FromStr for ResourceKind {
+    type Err = UnknownId;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        catalogue::parse(s)
+    }
+}
+
+impl fmt::Display for ResourceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
 }
 
 impl Resources {
+    /// Amount of a single resource
+    pub fn get(&self, kind: ResourceKind) -> f64 {
+        match kind {
+            ResourceKind::Wood => self.wood,
+            ResourceKind::Stone => self.stone,
+            ResourceKind::Food => self.food,
+            ResourceKind::Iron => self.iron,
+            ResourceKind::Gold => self.gold,
+            ResourceKind::Energy => self.energy,
+            ResourceKind::Science => self.science,
+            ResourceKind::Mana => self.mana,
+            ResourceKind::Tools => self.tools,
+            ResourceKind::Steel => self.steel,
+            ResourceKind::Coins => self.coins,
+        }
+    }
+
+    /// Mutable amount of a single resource
+    pub fn get_mut(&mut self, kind: ResourceKind) -> &mut f64 {
+        match kind {
+            ResourceKind::Wood => &mut self.wood,
+            ResourceKind::Stone => &mut self.stone,
+            ResourceKind::Food => &mut self.food,
+            ResourceKind::Iron => &mut self.iron,
+            ResourceKind::Gold => &mut self.gold,
+            ResourceKind::Energy => &mut self.energy,
+            ResourceKind::Science => &mut self.science,
+            ResourceKind::Mana => &mut self.mana,
+            ResourceKind::Tools => &mut self.tools,
+            ResourceKind::Steel => &mut self.steel,
+            ResourceKind::Coins => &mut self.coins,
+        }
+    }
+
     /// Add other resources to self
     pub fn add(&mut self, other: &Resources) {
         self.wood += other.wood;
@@ -32,6 +198,9 @@ impl Resources {
         self.energy += other.energy;
         self.science += other.science;
         self.mana += other.mana;
+        self.tools += other.tools;
+        self.steel += other.steel;
+        self.coins += other.coins;
     }
 
     /// Ensure all resources are non-negative
@@ -60,6 +229,20 @@ impl Resources {
         if self.mana < 0.0 {
             self.mana = 0.0;
         }
+        if self.tools < 0.0 {
+            self.tools = 0.0;
+        }
+        if self.steel < 0.0 {
+            self.steel = 0.0;
+        }
+        if self.coins < 0.0 {
+            self.coins = 0.0;
+        }
+    }
+
+    /// How much of each resource is still needed to afford `cost`
+    pub fn shortfall(&self, cost: &Resources) -> Resources {
+        cost.combine(self, |need, have| (need - have).max(0.0))
     }
 
     /// Subtract other resources if affordable
@@ -73,6 +256,9 @@ impl Resources {
             self.energy -= cost.energy;
             self.science -= cost.science;
             self.mana -= cost.mana;
+            self.tools -= cost.tools;
+            self.steel -= cost.steel;
+            self.coins -= cost.coins;
             true
         } else {
             false
@@ -89,6 +275,48 @@ impl Resources {
             && self.energy >= cost.energy
             && self.science >= cost.science
             && self.mana >= cost.mana
+            && self.tools >= cost.tools
+            && self.steel >= cost.steel
+            && self.coins >= cost.coins
+    }
+
+    /// Amounts in field order
+    pub fn values(&self) -> [f64; 11] {
+        [
+            self.wood,
+            self.stone,
+            self.food,
+            self.iron,
+            self.gold,
+            self.energy,
+            self.science,
+            self.mana,
+            self.tools,
+            self.steel,
+            self.coins,
+        ]
+    }
+
+    /// Apply `f` to every amount
+    pub fn map(&self, f: impl Fn(f64) -> f64) -> Resources {
+        self.combine(&Resources::default(), |v, _| f(v))
+    }
+
+    /// Combine each amount with the matching amount of `other`
+    pub fn combine(&self, other: &Resources, f: impl Fn(f64, f64) -> f64) -> Resources {
+        Resources {
+            wood: f(self.wood, other.wood),
+            stone: f(self.stone, other.stone),
+            food: f(self.food, other.food),
+            iron: f(self.iron, other.iron),
+            gold: f(self.gold, other.gold),
+            energy: f(self.energy, other.energy),
+            science: f(self.science, other.science),
+            mana: f(self.mana, other.mana),
+            tools: f(self.tools, other.tools),
+            steel: f(self.steel, other.steel),
+            coins: f(self.coins, other.coins),
+        }
     }
 
     /// Scale resources by factor
@@ -102,6 +330,9 @@ impl Resources {
             energy: self.energy * factor,
             science: self.science * factor,
             mana: self.mana * factor,
+            tools: self.tools * factor,
+            steel: self.steel * factor,
+            coins: self.coins * factor,
         }
     }
 }
@@ -117,10 +348,13 @@ pub fn res(wood: f64, stone: f64, food: f64, iron: f64, gold: f64) -> Resources
         energy: 0.0,
         science: 0.0,
         mana: 0.0,
+        tools: 0.0,
+        steel: 0.0,
+        coins: 0.0,
     }
 }
 
-/// Create a resource bundle with all fields specified
+/// Create a resource bundle with all gathered resources specified
 pub fn res_ext(
     wood: f64,
     stone: f64,
@@ -140,5 +374,8 @@ pub fn res_ext(
         energy,
         science,
         mana,
+        tools: 0.0,
+        steel: 0.0,
+        coins: 0.0,
     }
 }
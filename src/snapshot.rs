@@ -0,0 +1,85 @@
+use crate::population::PopulationStatus;
+use crate::research::Tech;
+use crate::resources::Resources;
+use serde::Serialize;
+
+/// Everything the UI renders in a frame
+#[derive(Serialize)]
+pub struct Snapshot {
+    /// Current stock of every resource
+    pub resources: Resources,
+    /// Net change of every resource per second
+    pub rates: Resources,
+    /// Every building type in display order
+    pub buildings: Vec<BuildingSnapshot>,
+    /// Every upgrade in display order
+    pub upgrades: Vec<UpgradeSnapshot>,
+    /// Every technology in display order
+    pub research: Vec<TechSnapshot>,
+    /// Workers and how they are assigned
+    pub population: PopulationStatus,
+    /// Prestige points and bonus
+    pub prestige: PrestigeSnapshot,
+    /// Log messages since the previous snapshot
+    pub log: Vec<String>,
+}
+
+/// State of one building type
+#[derive(Serialize)]
+pub struct BuildingSnapshot {
+    /// Identifier used by the JS API
+    pub id: &'static str,
+    /// Name shown to the player
+    pub name: &'static str,
+    /// Levels owned
+    pub count: u32,
+    /// Levels not paused
+    pub active: u32,
+    /// Cost of the next level
+    pub cost: Resources,
+    /// Whether the next level can be paid for now
+    pub affordable: bool,
+    /// Technology still missing, if the building is locked
+    pub requires: Option<Tech>,
+}
+
+/// State of one upgrade
+#[derive(Serialize)]
+pub struct UpgradeSnapshot {
+    /// Identifier used by the JS API
+    pub id: &'static str,
+    /// Name shown to the player
+    pub name: &'static str,
+    /// Levels bought
+    pub level: u32,
+    /// Cost of the next level
+    pub cost: Resources,
+    /// Whether the next level can be paid for now
+    pub affordable: bool,
+}
+
+/// State of one technology
+#[derive(Serialize)]
+pub struct TechSnapshot {
+    /// Identifier used by the JS API
+    pub id: &'static str,
+    /// Name shown to the player
+    pub name: &'static str,
+    /// Whether it has been researched
+    pub unlocked: bool,
+    /// Science needed to research it
+    pub cost: Resources,
+    /// Whether its cost can be paid now
+    pub affordable: bool,
+}
+
+/// Prestige progress
+#[derive(Serialize)]
+pub struct PrestigeSnapshot {
+    /// Points earned by earlier resets
+    pub points: u32,
+    /// Output multiplier the points grant
+    pub bonus_multiplier: f64,
+    /// Points a reset would earn right now
+    pub available: u32,
+}
@@ -0,0 +1,155 @@
+use crate::catalogue::{self, Catalogue, UnknownId};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Spells that can be cast with mana
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Spell {
+    /// Converts mana into gold
+    #[serde(alias = "Transmute")]
+    Transmute,
+    /// Instantly grants a minute of production
+    #[serde(alias = "TimeWarp")]
+    TimeWarp,
+    /// Protects farms from storms for a while
+    #[serde(alias = "Ward")]
+    Ward,
+    /// Temporarily boosts every building
+    #[serde(alias = "Blessing")]
+    Blessing,
+}
+
+impl Spell {
+    /// Every spell in display order
+    pub const ALL: [Spell; 4] = [
+        Spell::Transmute,
+        Spell::TimeWarp,
+        Spell::Ward,
+        Spell::Blessing,
+    ];
+}
+
+impl Catalogue for Spell {
+    const ENTRIES: &'static [Self] = &Self::ALL;
+
+    fn id(self) -> &'static str {
+        match self {
+            Spell::Transmute => "transmute",
+            Spell::TimeWarp => "time_warp",
+            Spell::Ward => "ward",
+            Spell::Blessing => "blessing",
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Spell::Transmute => "Transmute",
+            Spell::TimeWarp => "Time Warp",
+            Spell::Ward => "Ward",
+            Spell::Blessing => "Blessing",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Spell::Transmute => "Converts mana into gold",
+            Spell::TimeWarp => "Instantly grants a minute of production",
+            Spell::Ward => "Protects farms from storms for a while",
+            Spell::Blessing => "Temporarily boosts every building",
+        }
+    }
+}
+
+impl FromStr for Spell {
+    type Err = UnknownId;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        catalogue::parse(s)
+    }
+}
+
+impl fmt::Display for Spell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+/// Seconds of production granted by a time warp
+pub const TIME_WARP_SECONDS: f64 = 60.0;
+/// Gold gained per mana spent on transmutation
+pub const TRANSMUTE_RATE: f64 = 5.0;
+
+struct SpellInfo {
+    mana: f64,
+    cooldown: f64,
+}
+
+fn info(spell: Spell) -> SpellInfo {
+    match spell {
+        Spell::Transmute => SpellInfo {
+            mana: 10.0,
+            cooldown: 10.0,
+        },
+        Spell::TimeWarp => SpellInfo {
+            mana: 50.0,
+            cooldown: 300.0,
+        },
+        Spell::Ward => SpellInfo {
+            mana: 20.0,
+            cooldown: 120.0,
+        },
+        Spell::Blessing => SpellInfo {
+            mana: 30.0,
+            cooldown: 180.0,
+        },
+    }
+}
+
+/// Spell cooldowns
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct Spellbook {
+    cooldowns: HashMap<Spell, f64>,
+}
+
+impl Spellbook {
+    /// Mana needed to cast a spell
+    pub fn mana_cost(spell: Spell) -> f64 {
+        info(spell).mana
+    }
+
+    /// Game time in seconds until a spell can be cast again
+    pub fn cooldown(&self, spell: Spell) -> f64 {
+        self.cooldowns.get(&spell).copied().unwrap_or(0.0)
+    }
+
+    /// Remaining cooldown of every spell still recharging
+    pub fn cooldowns(&self) -> HashMap<Spell, f64> {
+        self.cooldowns.clone()
+    }
+
+    /// Count down cooldowns by elapsed game time
+    pub fn advance(&mut self, seconds: f64) {
+        self.cooldowns.retain(|_, left| {
+            *left -= seconds;
+            *left > 1e-9
+        });
+    }
+
+    /// Pay the mana for a spell and start its cooldown. Fails with a reason
+    /// if the spell is recharging or there is not enough mana.
+    pub fn begin_cast(&mut self, spell: Spell, mana: &mut f64) -> Result<(), String> {
+        let i = info(spell);
+        if self.cooldown(spell) > 0.0 {
+            return Err(format!("{} is recharging", spell.name()));
+        }
+        if *mana < i.mana {
+            return Err(format!("{} needs {} mana", spell.name(), i.mana));
+        }
+        *mana -= i.mana;
+        self.cooldowns.insert(spell, i.cooldown);
+        Ok(())
+    }
+}
@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+/// Counters tracked over the current run
+#[derive(Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Statistics {
+    /// Building levels constructed
+    pub buildings_built: u32,
+    /// Building levels sold back
+    pub buildings_sold: u32,
+    /// Technologies researched
+    pub techs_researched: u32,
+}
@@ -1,19 +1,116 @@
 use crate::achievements::Achievements;
-use crate::buildings::{BuildingType, Buildings};
-use crate::events::check_random_events;
+use crate::automation::{Automation, QueuedAction, AUTOMATION_PRESTIGE};
+use crate::buildings::{BuildingType, Buildings, SELL_REFUND_RATIO};
+use crate::catalogue::Catalogue;
+use crate::changes::{affordable_mask, ChangeTracker, Changes, ResourceSection, Section};
+use crate::effects::{ActiveEffect, EffectType, Effects};
+use crate::error::ActionError;
+use crate::estimates::{time_to_afford, AffordEstimates, CostEstimate};
+use crate::events::{chance_within, check_random_events};
+use crate::market::{Market, MarketPrice};
+use crate::modifiers::{apply, multiplier, Modifier};
+use crate::population::{Population, PopulationStatus};
+use crate::power::{self, PowerStatus};
+use crate::production::{solve, Production};
+use crate::quests::{QuestStatus, Quests};
+use crate::recipes::{self, Recipe, RecipeInfo};
 use crate::research::{Research, Tech};
 #[allow(unused_imports)]
-use crate::resources::{res, Resources};
+use crate::resources::{res, ResourceKind, Resources};
+use crate::snapshot::{
+    BuildingSnapshot, PrestigeSnapshot, Snapshot, TechSnapshot, UpgradeSnapshot,
+};
+use crate::spells::{Spell, Spellbook, TIME_WARP_SECONDS, TRANSMUTE_RATE};
+use crate::statistics::Statistics;
 use crate::upgrades::{UpgradeType, Upgrades};
 use base64::Engine;
-use rand::thread_rng;
+use rand::rngs::StdRng;
+use rand::{thread_rng, Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 
 /// Current save format version
-pub const SAVE_VERSION: u32 = 1;
+pub const SAVE_VERSION: u32 = 2;
+
+/// Most offline time caught up in one call
+pub const MAX_OFFLINE_SECONDS: f64 = 8.0 * 3600.0;
+
+/// Slowest and fastest game speed players can choose
+pub const GAME_SPEED_RANGE: (f64, f64) = (0.25, 10.0);
+
+/// Output of one building type and the modifiers behind it
+#[derive(Serialize)]
+pub struct BuildingYield {
+    /// Building type
+    pub building: BuildingType,
+    /// Levels owned
+    pub level: u32,
+    /// Levels running with enough workers
+    pub staffed: u32,
+    /// Yield per second of the staffed levels before modifiers
+    pub base: Resources,
+    /// Modifiers affecting this building
+    pub modifiers: Vec<Modifier>,
+    /// Upkeep per second of the staffed levels
+    pub upkeep: Resources,
+    /// Yield per second after modifiers and upkeep
+    pub total: Resources,
+}
+
+/// Contribution of one building type to a single resource
+#[derive(Serialize)]
+pub struct ResourceContribution {
+    /// Building type
+    pub building: BuildingType,
+    /// Levels running with enough workers
+    pub staffed: u32,
+    /// Amount per second before modifiers
+    pub base: f64,
+    /// Combined multiplier of the modifiers affecting this building
+    pub multiplier: f64,
+    /// Amount per second after modifiers and upkeep
+    pub total: f64,
+}
+
+/// Explanation of where a resource's yield comes from
+#[derive(Serialize)]
+pub struct YieldBreakdown {
+    /// Resource explained
+    pub resource: ResourceKind,
+    /// Buildings producing or consuming the resource
+    pub buildings: Vec<ResourceContribution>,
+    /// Modifiers that can affect the resource
+    pub modifiers: Vec<Modifier>,
+    /// Sum of building totals per second before input shortages
+    pub total: f64,
+}
+
+/// Outcome of casting a spell
+#[derive(Serialize)]
+pub struct CastResult {
+    /// Whether the spell was cast
+    pub success: bool,
+    /// What happened, or why the spell failed
+    pub message: String,
+    /// Resources gained, not counting the mana spent
+    pub gained: Resources,
+    /// Timed effect started by the spell
+    pub effect: Option<EffectType>,
+}
+
+/// Predicted outcome of a what-if simulation
+#[derive(Serialize)]
+pub struct Simulation {
+    /// Resources at the end of the simulated time
+    pub resources: Resources,
+    /// Net change of each resource per second at the end
+    pub rates: Resources,
+    /// Actions that could not be taken, with the reason
+    pub errors: Vec<ActionError>,
+}
 
 /// Game state containing all persistent data
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct GameState {
     /// Version of save
     pub version: u32,
@@ -29,18 +126,77 @@ pub struct GameState {
     pub achievements: Achievements,
     /// Prestige information
     pub prestige: crate::prestige::Prestige,
+    /// Counters for the current run
+    #[serde(default)]
+    pub statistics: Statistics,
+    /// Workers staffing buildings
+    #[serde(default)]
+    pub population: Population,
+    /// Timed buffs and debuffs
+    #[serde(default)]
+    pub effects: Effects,
+    /// Spell cooldowns
+    #[serde(default)]
+    pub spells: Spellbook,
+    /// Trade prices for gold
+    #[serde(default)]
+    pub market: Market,
+    /// Auto-builders and purchase queue
+    #[serde(default)]
+    pub automation: Automation,
+    /// Progress through quest chains
+    #[serde(default)]
+    pub quests: Quests,
     /// Pending event log messages
     #[serde(skip)]
     pub event_log: Vec<String>,
-    /// Chance of a random event each tick
+    /// Building types idled by unpaid upkeep
+    #[serde(skip)]
+    pub unpaid_upkeep: HashSet<BuildingType>,
+    /// Versions at which UI sections last changed
+    #[serde(skip)]
+    pub changes: ChangeTracker,
+    /// Chance of a random event each second
     #[serde(skip)]
     pub event_chance: f64,
+    /// Fraction of a building's cost refunded when sold
+    #[serde(skip, default = "default_sell_refund")]
+    pub sell_refund: f64,
     /// Tick rate in seconds
     pub tick_rate: f64,
+    /// Game seconds that pass per real second
+    #[serde(default = "default_speed")]
+    pub speed: f64,
+    /// Whether game time is stopped, for debugging
+    #[serde(skip)]
+    pub frozen: bool,
     /// Last update timestamp in seconds
     pub last_update: Option<f64>,
 }
 
+fn default_sell_refund() -> f64 {
+    SELL_REFUND_RATIO
+}
+
+fn default_speed() -> f64 {
+    1.0
+}
+
+/// Technology that must be researched before a building can be used
+fn required_tech(ty: BuildingType) -> Option<Tech> {
+    match ty {
+        BuildingType::Mine => Some(Tech::Mining),
+        BuildingType::Bakery => Some(Tech::Baking),
+        BuildingType::Generator | BuildingType::Battery => Some(Tech::Electricity),
+        BuildingType::Lab => Some(Tech::Education),
+        BuildingType::Shrine => Some(Tech::Alchemy),
+        BuildingType::Smelter => Some(Tech::Metallurgy),
+        BuildingType::Forge => Some(Tech::Smithing),
+        BuildingType::Mint => Some(Tech::Minting),
+        _ => None,
+    }
+}
+
 impl Default for GameState {
     fn default() -> Self {
         Self::new()
@@ -59,33 +215,300 @@ impl GameState {
             research: Research::default(),
             achievements: Achievements::default(),
             prestige: crate::prestige::Prestige::default(),
+            statistics: Statistics::default(),
+            population: Population::default(),
+            effects: Effects::default(),
+            spells: Spellbook::default(),
+            market: Market::default(),
+            automation: Automation::default(),
+            quests: Quests::default(),
             event_log: Vec::new(),
+            unpaid_upkeep: HashSet::new(),
+            changes: ChangeTracker::default(),
             event_chance: crate::events::FARM_LOSS_CHANCE,
+            sell_refund: SELL_REFUND_RATIO,
             tick_rate: 1.0,
+            speed: default_speed(),
+            frozen: false,
             last_update: None,
         }
     }
 
-    /// Compute building yield with upgrades
-    fn tick_yield(&self) -> Resources {
-        let mut r = self.buildings.total_yield();
-        let m = self.upgrades.multiplier(UpgradeType::Efficiency);
-        r = r.scale(m);
-        r = r.scale(self.prestige.bonus_multiplier());
-        if r.mana > 0.0 {
-            r.mana *= self.upgrades.multiplier(UpgradeType::AlchemyBoost);
+    /// Every active output modifier in the order they are collected
+    pub fn modifiers(&self) -> Vec<Modifier> {
+        let mut mods = self.upgrades.modifiers();
+        mods.extend(self.buildings.milestone_modifiers());
+        mods.extend(self.prestige.modifiers());
+        mods.extend(self.effects.modifiers());
+        mods
+    }
+
+    /// Output of each staffed building type and the modifiers behind it
+    pub fn building_breakdown(&self) -> Vec<BuildingYield> {
+        let mods = self.modifiers();
+        self.population
+            .staffed(&self.buildings)
+            .into_iter()
+            .map(|(ty, staffed)| {
+                let base = Buildings::yield_of(ty, staffed);
+                let upkeep = Buildings::upkeep_of(ty, staffed);
+                let mut total = apply(&mods, ty, &base);
+                total.add(&upkeep.scale(-1.0));
+                BuildingYield {
+                    building: ty,
+                    level: self.buildings.level(ty),
+                    staffed,
+                    base,
+                    modifiers: mods
+                        .iter()
+                        .filter(|m| m.building.is_none_or(|b| b == ty))
+                        .cloned()
+                        .collect(),
+                    upkeep,
+                    total,
+                }
+            })
+            .collect()
+    }
+
+    /// Explain the yield of a single resource
+    pub fn yield_breakdown(&self, kind: ResourceKind) -> YieldBreakdown {
+        let mods = self.modifiers();
+        let buildings: Vec<_> = self
+            .building_breakdown()
+            .into_iter()
+            .filter(|b| b.base.get(kind) != 0.0 || b.upkeep.get(kind) != 0.0)
+            .map(|b| ResourceContribution {
+                building: b.building,
+                staffed: b.staffed,
+                base: b.base.get(kind),
+                multiplier: multiplier(&mods, b.building, kind),
+                total: b.total.get(kind),
+            })
+            .collect();
+        YieldBreakdown {
+            resource: kind,
+            total: buildings.iter().map(|b| b.total).sum(),
+            buildings,
+            modifiers: mods
+                .into_iter()
+                .filter(|m| m.resource.is_none_or(|r| r == kind))
+                .collect(),
         }
-        r
+    }
+
+    /// Yield of each staffed building type with upgrades applied and upkeep
+    /// deducted
+    fn building_yields(&self) -> Vec<(BuildingType, Resources)> {
+        self.building_breakdown()
+            .into_iter()
+            .map(|b| (b.building, b.total))
+            .collect()
+    }
+
+    /// Production over the next second, throttled by the current stockpile
+    pub fn production(&self) -> Production {
+        self.production_over(1.0)
+    }
+
+    /// Production over `seconds`, throttled by the current stockpile.
+    /// Workers share food with the buildings that consume it.
+    fn production_over(&self, seconds: f64) -> Production {
+        let yields: Vec<_> = self
+            .building_yields()
+            .into_iter()
+            .map(|(ty, y)| (ty, y.scale(seconds)))
+            .collect();
+        solve(
+            &self.resources,
+            &yields,
+            &self.population.upkeep().scale(seconds),
+        )
     }
 
     /// Net change of each resource per second
     pub fn resource_rate(&self) -> Resources {
-        self.tick_yield().scale(1.0 / self.tick_rate)
+        self.production().net
+    }
+
+    /// Everything the UI renders in a frame. Drains the pending log.
+    pub fn snapshot(&mut self) -> Snapshot {
+        Snapshot {
+            resources: self.resources,
+            rates: self.resource_rate(),
+            buildings: self.building_snapshots(),
+            upgrades: self.upgrade_snapshots(),
+            research: self.tech_snapshots(),
+            population: self.population_status(),
+            prestige: PrestigeSnapshot {
+                points: self.prestige.points,
+                bonus_multiplier: self.prestige.bonus_multiplier(),
+                available: crate::prestige::Prestige::points_for(&self.resources),
+            },
+            log: std::mem::take(&mut self.event_log),
+        }
+    }
+
+    /// Count, cost and lock state of every building type
+    fn building_snapshots(&self) -> Vec<BuildingSnapshot> {
+        BuildingType::ALL
+            .iter()
+            .map(|&ty| {
+                let cost = self.buildings.cost(ty);
+                BuildingSnapshot {
+                    id: ty.id(),
+                    name: ty.name(),
+                    count: self.buildings.level(ty),
+                    active: self.buildings.active(ty),
+                    affordable: self.resources.can_afford(&cost),
+                    cost,
+                    requires: self.missing_tech(ty),
+                }
+            })
+            .collect()
+    }
+
+    /// Level and next cost of every upgrade
+    fn upgrade_snapshots(&self) -> Vec<UpgradeSnapshot> {
+        UpgradeType::ALL
+            .iter()
+            .map(|&ty| {
+                let cost = self.upgrades.cost(ty);
+                UpgradeSnapshot {
+                    id: ty.id(),
+                    name: ty.name(),
+                    level: self.upgrades.level(ty),
+                    affordable: self.resources.can_afford(&cost),
+                    cost,
+                }
+            })
+            .collect()
+    }
+
+    /// Research state of every technology
+    fn tech_snapshots(&self) -> Vec<TechSnapshot> {
+        Tech::ALL
+            .iter()
+            .map(|&tech| TechSnapshot {
+                id: tech.id(),
+                name: tech.name(),
+                unlocked: self.research.is_unlocked(tech),
+                cost: tech.cost(),
+                affordable: self.resources.can_afford(&tech.cost()),
+            })
+            .collect()
+    }
+
+    /// Seconds until every building, upgrade and unresearched technology
+    /// becomes affordable at current rates
+    pub fn afford_estimates(&self) -> AffordEstimates {
+        let rate = self.resource_rate();
+        let estimate = |id, name, cost| CostEstimate {
+            id,
+            name,
+            cost,
+            estimate: time_to_afford(&self.resources, &rate, &cost),
+        };
+        AffordEstimates {
+            buildings: BuildingType::ALL
+                .iter()
+                .map(|&ty| estimate(ty.id(), ty.name(), self.buildings.cost(ty)))
+                .collect(),
+            upgrades: UpgradeType::ALL
+                .iter()
+                .map(|&ty| estimate(ty.id(), ty.name(), self.upgrades.cost(ty)))
+                .collect(),
+            research: Tech::ALL
+                .iter()
+                .filter(|&&tech| !self.research.is_unlocked(tech))
+                .map(|&tech| estimate(tech.id(), tech.name(), tech.cost()))
+                .collect(),
+        }
+    }
+
+    /// Sections that changed after `version`. Mutations flag the sections
+    /// they touch, so only flagged sections are rebuilt here.
+    pub fn changes_since(&mut self, version: u32) -> Changes {
+        self.changes.commit();
+        let changed = |section| self.changes.changed_since(section, version);
+        Changes {
+            version: self.changes.version(),
+            resources: changed(Section::Resources).then(|| ResourceSection {
+                amounts: self.resources,
+                rates: self.resource_rate(),
+            }),
+            buildings: changed(Section::Buildings).then(|| self.building_snapshots()),
+            research: changed(Section::Research).then(|| self.tech_snapshots()),
+            achievements: changed(Section::Achievements).then(|| self.achievements_list()),
+        }
+    }
+
+    /// Flag `sections` for `changes_since`. A change to resources also flags
+    /// buildings and research if it changed which of them are affordable.
+    pub(crate) fn mark_changed(&mut self, sections: &[Section]) {
+        for &section in sections {
+            self.changes.mark(section);
+        }
+        if sections.contains(&Section::Resources) {
+            let buildings = affordable_mask(
+                BuildingType::ALL.iter().map(|&ty| self.buildings.cost(ty)),
+                &self.resources,
+            );
+            let research = affordable_mask(Tech::ALL.iter().map(|t| t.cost()), &self.resources);
+            self.changes.mark_affordable(Section::Buildings, buildings);
+            self.changes.mark_affordable(Section::Research, research);
+        }
+    }
+
+    /// Energy supply, demand and storage of the grid
+    pub fn power_status(&self) -> PowerStatus {
+        power::status(
+            &self.building_yields(),
+            &self.production_over(self.tick_rate),
+            self.resources.energy,
+            power::storage_capacity(&self.buildings),
+        )
+    }
+
+    /// Demand per second that the stockpile and producers cannot cover
+    pub fn shortages(&self) -> Resources {
+        self.production().shortages
+    }
+
+    /// Log buildings that go idle because their upkeep cannot be paid at
+    /// all, and again once they run. Buildings merely rationed stay quiet.
+    fn report_upkeep(&mut self, p: &Production) {
+        for ty in BuildingType::ALL {
+            if Buildings::upkeep_of(ty, 1)
+                .values()
+                .iter()
+                .all(|&v| v == 0.0)
+            {
+                continue;
+            }
+            let fraction = match p.utilization.get(&ty) {
+                Some(&fraction) => fraction,
+                None => {
+                    // None left running, so nothing to report
+                    self.unpaid_upkeep.remove(&ty);
+                    continue;
+                }
+            };
+            if fraction == 0.0 {
+                if self.unpaid_upkeep.insert(ty) {
+                    self.event_log
+                        .push(format!("{} upkeep unpaid, production stalled", ty.name()));
+                }
+            } else if self.unpaid_upkeep.remove(&ty) {
+                self.event_log
+                    .push(format!("{} upkeep paid, production resumed", ty.name()));
+            }
+        }
     }
 
     /// Perform a prestige reset gaining permanent bonuses
     pub fn prestige(&mut self) {
-        let gained = ((self.resources.gold / 1e6).sqrt().floor()) as u32;
+        let gained = crate::prestige::Prestige::points_for(&self.resources);
         if gained > 0 {
             self.prestige.points += gained;
         }
@@ -95,131 +518,490 @@ impl GameState {
         self.upgrades = base.upgrades;
         self.research = base.research;
         self.achievements = base.achievements;
+        self.statistics = base.statistics;
+        self.population = base.population;
+        self.effects = base.effects;
+        self.spells = base.spells;
+        // Quest chains start over with the buildings and research they
+        // track, so each run gets their guidance and rewards again
+        self.quests = base.quests;
+        self.automation.clear_queue();
         self.last_update = base.last_update;
+        self.changes.mark_all();
+    }
+
+    /// Run buildings for one tick
+    fn produce(&mut self) {
+        let p = self.production_over(self.tick_rate);
+        let fed = p.upkeep_met >= 1.0;
+        self.report_upkeep(&p);
+        let stored = self.resources.energy;
+        self.resources.add(&p.net);
+        self.resources.clamp_non_negative();
+        // Storage only turns away new energy, so a stock above capacity from
+        // before batteries existed drains down to it rather than vanishing
+        let capacity = power::storage_capacity(&self.buildings).max(stored);
+        self.resources.energy = self.resources.energy.min(capacity);
+        if self.population.starve(fed, self.tick_rate) {
+            self.event_log
+                .push("A worker left for lack of food".to_string());
+        } else {
+            self.population
+                .grow(&self.buildings, &mut self.resources, self.tick_rate);
+        }
+    }
+
+    /// Cast a spell by name, spending mana and starting its cooldown
+    pub fn cast(&mut self, name: String) -> CastResult {
+        let mut result = CastResult {
+            success: false,
+            message: String::new(),
+            gained: Resources::default(),
+            effect: None,
+        };
+        let spell = match name.parse::<Spell>() {
+            Ok(spell) => spell,
+            Err(_) => {
+                result.message = format!("Unknown spell {name}");
+                return result;
+            }
+        };
+        if let Err(reason) = self.spells.begin_cast(spell, &mut self.resources.mana) {
+            result.message = reason;
+            return result;
+        }
+        let before = self.resources;
+        match spell {
+            Spell::Transmute => {
+                self.resources.gold += Spellbook::mana_cost(spell) * TRANSMUTE_RATE;
+            }
+            Spell::TimeWarp => {
+                let ticks = (TIME_WARP_SECONDS / self.tick_rate).floor() as u64;
+                for _ in 0..ticks {
+                    self.produce();
+                }
+            }
+            Spell::Ward => result.effect = Some(EffectType::Ward),
+            Spell::Blessing => result.effect = Some(EffectType::Blessing),
+        }
+        if let Some(effect) = result.effect {
+            self.effects.add(effect);
+        }
+        result.gained = self.resources;
+        result.gained.add(&before.scale(-1.0));
+        result.success = true;
+        result.message = format!("Cast {}", spell.name());
+        self.mark_changed(&[Section::Resources]);
+        result
+    }
+
+    /// Seconds until each recharging spell can be cast again
+    pub fn spell_cooldowns(&self) -> HashMap<Spell, f64> {
+        self.spells.cooldowns()
     }
 
     /// Advance the game by delta seconds
     pub fn tick(&mut self, now: f64) {
+        if self.frozen {
+            self.last_update = Some(now);
+            return;
+        }
         if let Some(prev) = self.last_update {
-            let elapsed = (now - prev).min(8.0 * 3600.0);
-            let ticks = (elapsed / self.tick_rate).floor() as u64;
-            for _ in 0..ticks {
-                let y = self.tick_yield();
-                self.resources.add(&y);
-                self.resources.clamp_non_negative();
-                if let Some(msg) = check_random_events(
-                    &mut self.buildings,
-                    &mut self.resources,
-                    &mut thread_rng(),
-                    self.event_chance,
-                ) {
-                    self.event_log.push(msg);
-                }
-                let new_ach = self.achievements.check(&self.buildings, &self.research);
-                self.event_log.extend(new_ach);
-            }
-            self.last_update = Some(prev + ticks as f64 * self.tick_rate);
-            if self.last_update.unwrap() < now {
-                self.last_update = Some(now);
-            }
+            let elapsed = now - prev;
+            let ticks = (elapsed.min(MAX_OFFLINE_SECONDS) * self.speed / self.tick_rate).floor();
+            self.run_ticks(ticks as u64);
+            // Carry the unused part of a tick over to the next call, unless
+            // offline time was cut short
+            self.last_update = if elapsed > MAX_OFFLINE_SECONDS {
+                Some(now)
+            } else {
+                Some(prev + ticks * self.tick_rate / self.speed)
+            };
         } else {
             self.last_update = Some(now);
         }
     }
 
-    /// Build a building by name
-    pub fn build(&mut self, name: String) -> bool {
-        let ty = match name.as_str() {
-            "farm" => BuildingType::Farm,
-            "lumber_mill" => BuildingType::LumberMill,
-            "quarry" => BuildingType::Quarry,
-            "mine" => BuildingType::Mine,
-            "bakery" => BuildingType::Bakery,
-            "generator" => BuildingType::Generator,
-            "lab" => BuildingType::Lab,
-            "shrine" => BuildingType::Shrine,
-            _ => return false,
+    /// Set how many game seconds pass per real second, clamped to
+    /// `GAME_SPEED_RANGE`. NaN resets to normal speed. Returns the speed
+    /// applied.
+    pub fn set_speed(&mut self, speed: f64) -> f64 {
+        let (min, max) = GAME_SPEED_RANGE;
+        self.speed = if speed.is_nan() {
+            default_speed()
+        } else {
+            speed.clamp(min, max)
         };
+        self.speed
+    }
+
+    /// Run the given number of whole ticks
+    pub(crate) fn run_ticks(&mut self, ticks: u64) {
+        self.run_ticks_with(ticks, &mut thread_rng());
+    }
+
+    /// Run whole ticks drawing market drift and random events from `rng`
+    fn run_ticks_with<R: Rng>(&mut self, ticks: u64, rng: &mut R) {
+        for _ in 0..ticks {
+            self.produce();
+            if self.automation_unlocked() {
+                self.run_automation();
+            }
+            self.spells.advance(self.tick_rate);
+            self.market.update(rng, self.tick_rate);
+            for effect in self.effects.advance(self.tick_rate) {
+                self.event_log.push(format!("{} has ended", effect.name()));
+            }
+            if let Some(msg) = check_random_events(
+                &mut self.buildings,
+                &mut self.resources,
+                &mut self.effects,
+                rng,
+                chance_within(self.event_chance, self.tick_rate),
+            ) {
+                // Some events destroy buildings
+                self.event_log.push(msg);
+                self.changes.mark(Section::Buildings);
+            }
+            self.check_achievements();
+            let done = self
+                .quests
+                .check(&self.buildings, &self.research, &mut self.resources);
+            self.event_log.extend(done);
+        }
+        if ticks > 0 {
+            self.mark_changed(&[Section::Resources]);
+        }
+    }
+
+    /// Predict the outcome of taking `actions` now and then waiting
+    /// `seconds`, at most `MAX_OFFLINE_SECONDS`, without touching this
+    /// state. Random events are disabled and market drift uses a fixed seed,
+    /// so the same inputs always give the same prediction.
+    pub fn simulate(&self, actions: &[QueuedAction], seconds: f64) -> Simulation {
+        let mut sim = self.clone();
+        sim.event_chance = 0.0;
+        let mut errors = Vec::new();
+        for &action in actions {
+            let result = match action {
+                QueuedAction::Build { building, count } => {
+                    (0..count).try_for_each(|_| sim.build_type(building))
+                }
+                QueuedAction::Research { tech } => sim.research(tech),
+                QueuedAction::Upgrade { upgrade } => {
+                    sim.upgrades.purchase(upgrade, &mut sim.resources)
+                }
+            };
+            errors.extend(result.err());
+        }
+        let seconds = seconds.clamp(0.0, MAX_OFFLINE_SECONDS);
+        let ticks = (seconds / sim.tick_rate).floor() as u64;
+        sim.run_ticks_with(ticks, &mut StdRng::seed_from_u64(0));
+        Simulation {
+            resources: sim.resources,
+            rates: sim.resource_rate(),
+            errors,
+        }
+    }
+
+    /// Build a building by name
+    pub fn build(&mut self, name: String) -> Result<(), ActionError> {
+        self.build_type(name.parse()?)
+    }
+
+    /// Build one level of a building if unlocked and affordable
+    fn build_type(&mut self, ty: BuildingType) -> Result<(), ActionError> {
         // Check research requirements
-        if matches!(ty, BuildingType::Mine) && !self.research.is_unlocked(Tech::Mining) {
-            return false;
+        if let Some(requires) = self.missing_tech(ty) {
+            return Err(ActionError::Locked { requires });
         }
-        if matches!(ty, BuildingType::Bakery) && !self.research.is_unlocked(Tech::Baking) {
-            return false;
+        self.buildings.build(ty, &mut self.resources)?;
+        self.statistics.buildings_built += 1;
+        self.mark_changed(&[Section::Resources, Section::Buildings]);
+        Ok(())
+    }
+
+    /// Research a technology using science
+    pub fn research(&mut self, tech: Tech) -> Result<(), ActionError> {
+        self.research.try_unlock(tech, &mut self.resources)?;
+        self.statistics.techs_researched += 1;
+        self.event_log.push(format!("Researched {}", tech.name()));
+        // Research unlocks buildings as well
+        self.mark_changed(&[Section::Resources, Section::Buildings, Section::Research]);
+        self.check_achievements();
+        Ok(())
+    }
+
+    /// Sell up to `amount` buildings by name, refunding `sell_refund` of the
+    /// cost paid for each level. Returns the number of buildings sold.
+    pub fn sell(&mut self, name: String, amount: u32) -> u32 {
+        let Ok(ty) = name.parse::<BuildingType>() else {
+            return 0;
+        };
+        if self.is_locked(ty) {
+            return 0;
         }
-        if matches!(ty, BuildingType::Generator) && !self.research.is_unlocked(Tech::Electricity) {
-            return false;
+        let sold = self
+            .buildings
+            .sell(ty, amount, &mut self.resources, self.sell_refund);
+        if sold > 0 {
+            self.statistics.buildings_sold += sold;
+            self.mark_changed(&[Section::Resources, Section::Buildings]);
+            self.check_achievements();
         }
-        if matches!(ty, BuildingType::Lab) && !self.research.is_unlocked(Tech::Education) {
-            return false;
+        sold
+    }
+
+    /// Whether auto-builders and the purchase queue are available
+    pub fn automation_unlocked(&self) -> bool {
+        self.research.is_unlocked(Tech::Automation) || self.prestige.points >= AUTOMATION_PRESTIGE
+    }
+
+    /// Attempt the front of the purchase queue, then let each auto-builder
+    /// buy one level if its cost fits within the spend limit
+    fn run_automation(&mut self) {
+        if let Some(action) = self.automation.front() {
+            let result = match action {
+                QueuedAction::Build { building, .. } => self.build_type(building),
+                QueuedAction::Research { tech } => self.research(tech),
+                QueuedAction::Upgrade { upgrade } => {
+                    self.upgrades.purchase(upgrade, &mut self.resources)
+                }
+            };
+            match result {
+                // A tech researched by hand in the meantime counts as done
+                Ok(()) | Err(ActionError::AlreadyOwned) => self.automation.complete_front(),
+                // Waiting on research would hold up everything behind it
+                Err(err @ ActionError::Locked { .. }) => {
+                    self.automation.drop_front();
+                    if let QueuedAction::Build { building, .. } = action {
+                        self.event_log
+                            .push(format!("Removed {} from the queue: {err}", building.name()));
+                    }
+                }
+                Err(_) => {}
+            }
+        }
+        for ty in BuildingType::ALL {
+            let fraction = self.automation.auto_build(ty);
+            if fraction > 0.0
+                && self
+                    .resources
+                    .scale(fraction)
+                    .can_afford(&self.buildings.cost(ty))
+            {
+                let _ = self.build_type(ty);
+            }
+        }
+    }
+
+    /// Let the auto-builder for a building spend up to `fraction` of each
+    /// resource per level
+    pub fn set_auto_build(&mut self, name: String, fraction: f64) -> bool {
+        match name.parse::<BuildingType>() {
+            Ok(ty) if self.automation_unlocked() => {
+                self.automation.set_auto_build(ty, fraction);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Queue `count` levels of a building by name
+    pub fn queue_build(&mut self, name: String, count: u32) -> bool {
+        match name.parse::<BuildingType>() {
+            Ok(building) if count > 0 && self.automation_unlocked() => {
+                self.automation
+                    .enqueue(QueuedAction::Build { building, count });
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Queue researching a technology by name
+    pub fn queue_research(&mut self, name: String) -> bool {
+        match name.parse::<Tech>() {
+            Ok(tech) if self.automation_unlocked() => {
+                self.automation.enqueue(QueuedAction::Research { tech });
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Queue one level of an upgrade by name
+    pub fn queue_upgrade(&mut self, name: String) -> bool {
+        match name.parse::<UpgradeType>() {
+            Ok(upgrade) if self.automation_unlocked() => {
+                self.automation.enqueue(QueuedAction::Upgrade { upgrade });
+                true
+            }
+            _ => false,
         }
-        if matches!(ty, BuildingType::Shrine) && !self.research.is_unlocked(Tech::Alchemy) {
-            return false;
+    }
+
+    /// Whether the research required for a building is still missing
+    fn is_locked(&self, ty: BuildingType) -> bool {
+        self.missing_tech(ty).is_some()
+    }
+
+    /// Technology required for a building that has not been researched yet
+    fn missing_tech(&self, ty: BuildingType) -> Option<Tech> {
+        required_tech(ty).filter(|&tech| !self.research.is_unlocked(tech))
+    }
+
+    /// Record newly unlocked achievements in the event log
+    fn check_achievements(&mut self) {
+        let new_ach = self
+            .achievements
+            .check(&self.buildings, &self.research, &self.statistics);
+        if !new_ach.is_empty() {
+            self.changes.mark(Section::Achievements);
         }
-        self.buildings.build(ty, &mut self.resources)
+        self.event_log.extend(new_ach);
     }
 
     /// Get the current cost to build the next level of a building by name
     pub fn build_cost(&self, name: String) -> Resources {
-        let ty = match name.as_str() {
-            "farm" => BuildingType::Farm,
-            "lumber_mill" => BuildingType::LumberMill,
-            "quarry" => BuildingType::Quarry,
-            "mine" => BuildingType::Mine,
-            "bakery" => BuildingType::Bakery,
-            "generator" => BuildingType::Generator,
-            "lab" => BuildingType::Lab,
-            "shrine" => BuildingType::Shrine,
-            _ => return Resources::default(),
+        let Ok(ty) = name.parse::<BuildingType>() else {
+            return Resources::default();
         };
         self.buildings.cost(ty)
     }
 
+    /// Purchase the next level of an upgrade by name
+    pub fn buy_upgrade(&mut self, name: String) -> Result<(), ActionError> {
+        self.upgrades.purchase(name.parse()?, &mut self.resources)?;
+        self.mark_changed(&[Section::Resources]);
+        Ok(())
+    }
+
+    /// Cost of the next level of an upgrade by name
+    pub fn upgrade_cost(&self, name: String) -> Resources {
+        let Ok(ty) = name.parse::<UpgradeType>() else {
+            return Resources::default();
+        };
+        self.upgrades.cost(ty)
+    }
+
+    /// Craft a recipe by name `times` times
+    pub fn craft(&mut self, name: String, times: u32) -> bool {
+        let crafted = match name.parse::<Recipe>() {
+            Ok(recipe) => recipes::craft(recipe, times, &mut self.resources),
+            Err(_) => false,
+        };
+        if crafted {
+            self.mark_changed(&[Section::Resources]);
+        }
+        crafted
+    }
+
+    /// Ingredients and products of every recipe
+    pub fn recipes(&self) -> Vec<RecipeInfo> {
+        Recipe::ALL.iter().map(|&r| recipes::info(r)).collect()
+    }
+
+    /// Current market prices of every tradable resource
+    pub fn market_prices(&self) -> Vec<MarketPrice> {
+        self.market.prices()
+    }
+
+    /// Sell a resource by name for gold. Returns the gold received, or 0 if
+    /// the trade failed.
+    pub fn market_sell(&mut self, name: String, amount: f64) -> f64 {
+        let gold = name
+            .parse::<ResourceKind>()
+            .ok()
+            .and_then(|kind| self.market.sell(kind, amount, &mut self.resources))
+            .unwrap_or(0.0);
+        if gold > 0.0 {
+            self.mark_changed(&[Section::Resources]);
+        }
+        gold
+    }
+
+    /// Buy a resource by name with gold. Returns the gold spent, or 0 if
+    /// the trade failed.
+    pub fn market_buy(&mut self, name: String, amount: f64) -> f64 {
+        let gold = name
+            .parse::<ResourceKind>()
+            .ok()
+            .and_then(|kind| self.market.buy(kind, amount, &mut self.resources))
+            .unwrap_or(0.0);
+        if gold > 0.0 {
+            self.mark_changed(&[Section::Resources]);
+        }
+        gold
+    }
+
     /// Number of buildings of the given type
     pub fn building_count(&self, name: String) -> u32 {
-        let ty = match name.as_str() {
-            "farm" => BuildingType::Farm,
-            "lumber_mill" => BuildingType::LumberMill,
-            "quarry" => BuildingType::Quarry,
-            "mine" => BuildingType::Mine,
-            "bakery" => BuildingType::Bakery,
-            "generator" => BuildingType::Generator,
-            "lab" => BuildingType::Lab,
-            "shrine" => BuildingType::Shrine,
-            _ => return 0,
+        let Ok(ty) = name.parse::<BuildingType>() else {
+            return 0;
         };
         self.buildings.level(ty)
     }
 
+    /// Number of running buildings of the given type
+    pub fn active_count(&self, name: String) -> u32 {
+        let Ok(ty) = name.parse::<BuildingType>() else {
+            return 0;
+        };
+        self.buildings.active(ty)
+    }
+
+    /// Run `count` buildings of the given type and pause the rest. Returns
+    /// the resulting number of running buildings.
+    pub fn set_active(&mut self, name: String, count: u32) -> u32 {
+        let Ok(ty) = name.parse::<BuildingType>() else {
+            return 0;
+        };
+        self.mark_changed(&[Section::Resources, Section::Buildings]);
+        self.buildings.set_active(ty, count)
+    }
+
+    /// Current workforce summary
+    pub fn population_status(&self) -> PopulationStatus {
+        self.population.status(&self.buildings)
+    }
+
+    /// Set which building types are staffed first from a comma separated
+    /// list of names. Returns false if any name is unknown.
+    pub fn set_worker_priority(&mut self, names: String) -> bool {
+        let priority: Result<Vec<BuildingType>, _> = names
+            .split(',')
+            .map(str::trim)
+            .filter(|n| !n.is_empty())
+            .map(str::parse)
+            .collect();
+        match priority {
+            Ok(priority) => {
+                self.population.priority = priority;
+                self.mark_changed(&[Section::Resources]);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
     /// Get resource by name
     pub fn get_resource(&self, name: String) -> f64 {
-        match name.as_str() {
-            "wood" => self.resources.wood,
-            "stone" => self.resources.stone,
-            "food" => self.resources.food,
-            "iron" => self.resources.iron,
-            "gold" => self.resources.gold,
-            "energy" => self.resources.energy,
-            "science" => self.resources.science,
-            "mana" => self.resources.mana,
-            _ => 0.0,
-        }
+        name.parse::<ResourceKind>()
+            .map_or(0.0, |kind| self.resources.get(kind))
     }
 
     /// Net resource change per second by name
     pub fn get_resource_rate(&self, name: String) -> f64 {
-        let rates = self.resource_rate();
-        match name.as_str() {
-            "wood" => rates.wood,
-            "stone" => rates.stone,
-            "food" => rates.food,
-            "iron" => rates.iron,
-            "gold" => rates.gold,
-            "energy" => rates.energy,
-            "science" => rates.science,
-            "mana" => rates.mana,
-            _ => 0.0,
-        }
+        name.parse::<ResourceKind>()
+            .map_or(0.0, |kind| self.resource_rate().get(kind))
+    }
+
+    /// Explain the yield of a resource by name
+    pub fn yield_breakdown_by_name(&self, name: String) -> Option<YieldBreakdown> {
+        name.parse::<ResourceKind>()
+            .ok()
+            .map(|kind| self.yield_breakdown(kind))
     }
 
     /// Save state to base64 string
@@ -233,7 +1015,31 @@ impl GameState {
         let bytes = base64::engine::general_purpose::STANDARD
             .decode(data)
             .ok()?;
-        serde_json::from_slice(&bytes).ok()
+        let mut state: GameState = serde_json::from_slice(&bytes).ok()?;
+        state.migrate();
+        // Edited saves could otherwise stop time or run it backwards
+        state.set_speed(state.speed);
+        Some(state)
+    }
+
+    /// Bring a state loaded from an older save format up to date
+    fn migrate(&mut self) {
+        if self.version < 2 {
+            // Before population, every building ran without workers
+            self.population.settle(&self.buildings);
+        }
+        self.version = SAVE_VERSION;
+    }
+
+    /// Current quest of each unfinished chain
+    pub fn quest_status(&self) -> Vec<QuestStatus> {
+        self.quests
+            .status(&self.buildings, &self.research, &self.resources)
+    }
+
+    /// Timed effects currently running
+    pub fn active_effects(&self) -> Vec<ActiveEffect> {
+        self.effects.list()
     }
 
     /// Pop the next log message generated by events or achievements
@@ -254,6 +1060,8 @@ impl GameState {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::effects::EffectType;
+    use crate::population::{BASE_POPULATION_CAP, HOUSE_CAPACITY, MIN_POPULATION};
     use wasm_bindgen_test::wasm_bindgen_test;
 
     #[wasm_bindgen_test]
@@ -268,12 +1076,13 @@ mod tests {
     fn offline_progress() {
         let mut g = GameState::new();
         g.event_chance = 0.0;
-        g.resources = res(20.0, 20.0, 0.0, 0.0, 0.0);
-        assert!(g.build("farm".into()));
+        g.resources = res(20.0, 20.0, 5.0, 0.0, 0.0);
+        assert!(g.build("farm".into()).is_ok());
         let start = g.resources.food;
         g.tick(10.0);
         g.tick(20.0);
-        assert_eq!(g.resources.food, start + 10.0);
+        // The farm grows 10 food while five workers eat 5
+        assert_eq!(g.resources.food, start + 5.0);
     }
 
     #[wasm_bindgen_test]
@@ -282,12 +1091,643 @@ mod tests {
         g.event_chance = 0.0;
         g.resources = res(100.0, 100.0, 100.5, 100.0, 100.0);
         g.research.unlock(Tech::Baking);
-        assert!(g.build("bakery".into()));
-        g.resources.food = 0.5;
+        assert!(g.build("bakery".into()).is_ok());
+        g.resources.food = 1.0;
         g.tick(0.0);
         g.tick(1.0);
+        // Workers and the bakery split the food in proportion to demand
+        assert_eq!(g.resources.food, 0.0);
+        assert!((g.resources.gold - (100.0 + 0.2 * 2.0 / 3.0)).abs() < 1e-6);
+        assert_eq!(g.get_resource_rate("gold".into()), 0.0);
+        // The bakery and the workers both go without
+        assert_eq!(g.shortages().food, 1.5);
+    }
+
+    #[wasm_bindgen_test]
+    fn sell_refunds_previous_level_cost() {
+        let mut g = GameState::new();
+        g.resources = res(20.0, 20.0, 0.0, 0.0, 0.0);
+        assert!(g.build("farm".into()).is_ok());
+        assert_eq!(g.sell("farm".into(), 3), 1);
+        assert_eq!(g.building_count("farm".into()), 0);
+        assert!((g.resources.wood - 15.0).abs() < 1e-6);
+        assert!((g.resources.stone - 15.0).abs() < 1e-6);
+        assert_eq!(g.statistics.buildings_sold, 1);
+        assert!(g.achievements_list().contains(&"Demolition".to_string()));
+    }
+
+    #[wasm_bindgen_test]
+    fn sell_respects_research() {
+        let mut g = GameState::new();
+        g.buildings.increment(BuildingType::Mine);
+        assert_eq!(g.sell("mine".into(), 1), 0);
+        g.research.unlock(Tech::Mining);
+        assert_eq!(g.sell("mine".into(), 1), 1);
+    }
+
+    #[wasm_bindgen_test]
+    fn research_applies_side_effects() {
+        let mut g = GameState::new();
+        g.resources.science = 150.0;
+        assert!(g.research(Tech::Mining).is_ok());
+        assert_eq!(g.resources.science, 50.0);
+        assert_eq!(g.statistics.techs_researched, 1);
+        assert!(g.event_log.contains(&"Researched Mining".to_string()));
+        assert!(g
+            .achievements_list()
+            .contains(&"Discovered Mining".to_string()));
+        assert_eq!(g.research(Tech::Mining), Err(ActionError::AlreadyOwned));
+        assert_eq!(g.resources.science, 50.0);
+        assert_eq!(g.statistics.techs_researched, 1);
+        assert!(matches!(
+            g.research(Tech::Baking),
+            Err(ActionError::Insufficient { .. })
+        ));
+    }
+
+    #[wasm_bindgen_test]
+    fn snapshot_reports_state_and_drains_log() {
+        let mut g = GameState::new();
+        g.resources = res(20.0, 20.0, 0.0, 0.0, 0.0);
+        assert!(g.build("farm".into()).is_ok());
+        g.event_log.push("hello".into());
+        let snap = g.snapshot();
+        // The farm grows 1 food per second and five workers eat 0.5
+        assert_eq!(snap.rates.food, 0.5);
+        let farm = &snap.buildings[0];
+        assert_eq!((farm.id, farm.count, farm.requires), ("farm", 1, None));
+        assert!(!farm.affordable);
+        let mine = snap.buildings.iter().find(|b| b.id == "mine").unwrap();
+        assert_eq!(mine.requires, Some(Tech::Mining));
+        assert_eq!(snap.log, ["hello"]);
+        assert!(g.snapshot().log.is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    fn changes_since_reports_only_changed_sections() {
+        let mut g = GameState::new();
+        g.event_chance = 0.0;
+        g.resources = res(20.0, 20.0, 0.0, 0.0, 0.0);
+        let first = g.changes_since(0);
+        assert!(first.resources.is_some() && first.achievements.is_some());
+        // Nothing is recomputed without a flagged change, so even a write
+        // that bypasses the game's methods goes unreported
+        g.resources.gold = 1000.0;
+        let idle = g.changes_since(first.version);
+        assert_eq!(idle.version, first.version);
+        assert!(idle.resources.is_none() && idle.buildings.is_none());
+        assert!(idle.research.is_none() && idle.achievements.is_none());
+        g.resources.science = 100.0;
+        assert!(g.research(Tech::Mining).is_ok());
+        let after = g.changes_since(first.version);
+        assert!(after.research.is_some() && after.resources.is_some());
+        // Mines are no longer locked
+        assert!(after.buildings.is_some());
+        assert!(after.achievements.is_some());
+        let next = g.changes_since(after.version);
+        assert!(next.resources.is_none() && next.research.is_none());
+        g.run_ticks(1);
+        let ticked = g.changes_since(after.version);
+        assert!(ticked.resources.is_some());
+        assert!(ticked.buildings.is_none() && ticked.research.is_none());
+    }
+
+    #[wasm_bindgen_test]
+    fn simulate_leaves_state_untouched() {
+        let mut g = GameState::new();
+        g.resources = res(20.0, 20.0, 10.0, 0.0, 0.0);
+        let build = QueuedAction::Build {
+            building: BuildingType::Farm,
+            count: 1,
+        };
+        let sim = g.simulate(&[build], 60.0);
+        assert!(sim.errors.is_empty());
+        assert_eq!(sim.resources.food, 40.0);
+        assert_eq!(sim.rates.food, 0.5);
+        assert_eq!(g.building_count("farm".into()), 0);
+        assert_eq!(g.resources.wood, 20.0);
+        let twice = QueuedAction::Build {
+            building: BuildingType::Farm,
+            count: 2,
+        };
+        let sim = g.simulate(&[twice], 0.0);
+        assert!(matches!(sim.errors[..], [ActionError::Insufficient { .. }]));
+        // Long previews stop at the offline limit and repeat exactly
+        g.tick_rate = 10.0;
+        let capped = g.simulate(&[build], MAX_OFFLINE_SECONDS);
+        assert_eq!(
+            g.simulate(&[build], f64::INFINITY).resources,
+            capped.resources
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn estimates_use_current_rates() {
+        let mut g = GameState::new();
+        g.resources = res(0.0, 20.0, 0.0, 0.0, 0.0);
+        g.buildings.increment(BuildingType::LumberMill);
+        let est = g.afford_estimates();
+        let farm = est.buildings.iter().find(|e| e.id == "farm").unwrap();
+        assert_eq!(farm.estimate.seconds, Some(10.0));
+        assert_eq!(farm.estimate.bottleneck, Some(ResourceKind::Wood));
+        let mining = est.research.iter().find(|e| e.id == "mining").unwrap();
+        assert_eq!(mining.estimate.seconds, None);
+        assert_eq!(mining.estimate.bottleneck, Some(ResourceKind::Science));
+    }
+
+    #[wasm_bindgen_test]
+    fn speed_scales_elapsed_time() {
+        let mut g = GameState::new();
+        g.event_chance = 0.0;
+        g.buildings.increment(BuildingType::Farm);
+        g.resources.food = 10.0;
+        assert_eq!(g.set_speed(100.0), GAME_SPEED_RANGE.1);
+        g.set_speed(2.0);
+        g.tick(0.0);
+        g.tick(30.0);
+        assert_eq!(g.resources.food, 40.0);
+        assert_eq!(g.set_speed(f64::NAN), 1.0);
+        let mut json = serde_json::to_value(&g).unwrap();
+        json["speed"] = 0.into();
+        let data =
+            base64::engine::general_purpose::STANDARD.encode(serde_json::to_vec(&json).unwrap());
+        let loaded = GameState::load_string(&data).unwrap();
+        assert_eq!(loaded.speed, GAME_SPEED_RANGE.0);
+    }
+
+    #[wasm_bindgen_test]
+    fn tick_rate_does_not_change_economy() {
+        let run = |tick_rate, food, bakery, seconds| {
+            let mut g = GameState::new();
+            g.event_chance = 0.0;
+            g.tick_rate = tick_rate;
+            g.resources = res(0.0, 0.0, food, 0.0, 0.0);
+            g.buildings.increment(BuildingType::Farm);
+            if bakery {
+                g.research.unlock(Tech::Baking);
+                g.buildings.increment(BuildingType::Bakery);
+            }
+            g.tick(0.0);
+            g.tick(seconds);
+            g.resources
+        };
+        // Starting with no food, the bakery relies on the farm's output
+        // within each tick
+        for food in [3.0, 0.0] {
+            let slow = run(1.0, food, true, 10.0);
+            let fast = run(0.2, food, true, 10.0);
+            assert!((slow.gold - fast.gold).abs() < 1e-9, "food {food}");
+            assert!((slow.food - fast.food).abs() < 1e-9, "food {food}");
+        }
+        // Workers eat from the same tick's harvest too
+        for tick_rate in [0.2, 1.0, 5.0, 10.0] {
+            let food = run(tick_rate, 0.0, false, 20.0).food;
+            assert!((food - 10.0).abs() < 1e-9, "tick rate {tick_rate}");
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn paused_buildings_idle() {
+        let mut g = GameState::new();
+        g.event_chance = 0.0;
+        for _ in 0..4 {
+            g.buildings.increment(BuildingType::Farm);
+        }
+        assert_eq!(g.set_active("farm".into(), 1), 1);
+        assert_eq!(g.active_count("farm".into()), 1);
+        // Net of the 0.5 food the workers eat
+        assert_eq!(g.get_resource_rate("food".into()), 0.5);
+        g.buildings.decrement(BuildingType::Farm, 2);
+        assert_eq!(g.active_count("farm".into()), 1);
+        assert_eq!(g.set_active("farm".into(), 10), 2);
+        assert_eq!(g.get_resource_rate("food".into()), 1.5);
+    }
+
+    #[wasm_bindgen_test]
+    fn workers_limit_production() {
+        let mut g = GameState::new();
+        g.event_chance = 0.0;
+        for _ in 0..4 {
+            g.buildings.increment(BuildingType::Farm);
+            g.buildings.increment(BuildingType::LumberMill);
+        }
+        // Food rates are net of the 0.5 the workers eat
+        assert_eq!(g.get_resource_rate("food".into()), 3.5);
+        assert_eq!(g.get_resource_rate("wood".into()), 1.0);
+        assert!(g.set_worker_priority("lumber_mill".into()));
+        assert_eq!(g.get_resource_rate("food".into()), 0.5);
+        assert_eq!(g.get_resource_rate("wood".into()), 4.0);
+        assert_eq!(g.population_status().idle, 0);
+        assert!(!g.set_worker_priority("lumber_mill,castle".into()));
+    }
+
+    #[wasm_bindgen_test]
+    fn houses_let_population_grow() {
+        let mut g = GameState::new();
+        g.event_chance = 0.0;
+        g.buildings.increment(BuildingType::House);
+        g.resources.food = 15.0;
+        g.tick(0.0);
+        g.tick(3.0);
+        // The first house also completes a quest worth 20 food, and the
+        // growing workforce eats 0.5 + 0.6 + 0.7
+        let status = g.population_status();
+        assert_eq!(status.count, BASE_POPULATION_CAP + 3);
+        assert_eq!(status.cap, BASE_POPULATION_CAP + HOUSE_CAPACITY);
+        assert!((g.resources.food - 3.2).abs() < 1e-9);
+    }
+
+    #[wasm_bindgen_test]
+    fn hungry_workers_leave() {
+        let mut g = GameState::new();
+        g.event_chance = 0.0;
+        g.resources.food = 1.0;
+        g.tick(0.0);
+        g.tick(12.0);
         assert_eq!(g.resources.food, 0.0);
-        assert!((g.resources.gold - 100.2).abs() < 1e-6);
+        assert_eq!(g.population_status().count, BASE_POPULATION_CAP - 1);
+        assert_eq!(g.pop_log().unwrap(), "A worker left for lack of food");
+        g.resources.food = 10.0;
+        g.tick(30.0);
+        assert_eq!(g.population_status().count, BASE_POPULATION_CAP - 1);
+    }
+
+    #[wasm_bindgen_test]
+    fn fresh_game_recovers_from_starvation() {
+        let mut g = GameState::new();
+        g.event_chance = 0.0;
+        g.run_ticks(300);
+        assert_eq!(g.population_status().count, MIN_POPULATION);
+        // The last worker staffs a farm, whose surplus raises the rest
+        assert!(g.build("farm".into()).is_ok());
+        g.run_ticks(300);
+        assert_eq!(g.population_status().count, BASE_POPULATION_CAP);
+        assert!(g.resources.food > 0.0);
+    }
+
+    #[wasm_bindgen_test]
+    fn legacy_saves_staff_their_buildings() {
+        let mut g = GameState::new();
+        for _ in 0..6 {
+            g.buildings.increment(BuildingType::Farm);
+            g.buildings.increment(BuildingType::LumberMill);
+        }
+        let mut json = serde_json::to_value(&g).unwrap();
+        json["version"] = 1.into();
+        json.as_object_mut().unwrap().remove("population");
+        let data =
+            base64::engine::general_purpose::STANDARD.encode(serde_json::to_vec(&json).unwrap());
+        let loaded = GameState::load_string(&data).unwrap();
+        assert_eq!(loaded.version, SAVE_VERSION);
+        let status = loaded.population_status();
+        assert_eq!(status.idle, 0);
+        assert_eq!(status.count, status.cap);
+        assert_eq!(loaded.resource_rate().wood, 6.0);
+    }
+
+    #[wasm_bindgen_test]
+    fn unpaid_upkeep_idles_building() {
+        let mut g = GameState::new();
+        g.event_chance = 0.0;
+        g.buildings.increment(BuildingType::Generator);
+        g.tick(0.0);
+        g.tick(1.0);
+        assert_eq!(g.resources.energy, 0.0);
+        assert_eq!(
+            g.pop_log().unwrap(),
+            "Generator upkeep unpaid, production stalled"
+        );
+        g.resources.iron = 0.2;
+        g.tick(3.0);
+        assert!((g.resources.energy - 2.0).abs() < 1e-9);
+        assert!(g.resources.iron.abs() < 1e-9);
+        assert_eq!(
+            g.pop_log().unwrap(),
+            "Generator upkeep paid, production resumed"
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn rationed_upkeep_is_not_reported() {
+        let mut g = GameState::new();
+        g.event_chance = 0.0;
+        g.resources.food = 10.0;
+        g.buildings.increment(BuildingType::Generator);
+        g.buildings.increment(BuildingType::Generator);
+        g.buildings.increment(BuildingType::Shrine);
+        g.resources.iron = 0.1;
+        g.tick(0.0);
+        g.tick(1.0);
+        // The generators ran at half power; only the shrine stopped
+        assert_eq!(
+            g.pop_log().unwrap(),
+            "Shrine upkeep unpaid, production stalled"
+        );
+        assert_eq!(g.pop_log(), None);
+    }
+
+    #[wasm_bindgen_test]
+    fn building_upgrade_doubles_target_only() {
+        let mut g = GameState::new();
+        g.buildings.increment(BuildingType::Farm);
+        g.buildings.increment(BuildingType::LumberMill);
+        g.resources = res(100.0, 50.0, 0.0, 20.0, 0.0);
+        assert!(g.buy_upgrade("iron_plough".into()).is_ok());
+        // Doubled farm output, net of the workers' food
+        assert_eq!(g.get_resource_rate("food".into()), 1.5);
+        assert_eq!(g.get_resource_rate("wood".into()), 1.0);
+        assert!(g.upgrade_cost("iron_plough".into()).wood > 100.0);
+    }
+
+    #[wasm_bindgen_test]
+    fn milestones_double_output() {
+        let mut g = GameState::new();
+        g.population.count = 100;
+        for _ in 0..25 {
+            g.buildings.increment(BuildingType::Quarry);
+        }
+        assert_eq!(g.get_resource_rate("stone".into()), 50.0);
+        let quarry = &g.building_breakdown()[0];
+        assert_eq!(quarry.building, BuildingType::Quarry);
+        assert_eq!(quarry.modifiers[0].source, "Milestones");
+        assert_eq!(quarry.base.stone, 25.0);
+    }
+
+    #[wasm_bindgen_test]
+    fn boosted_consumers_use_more_input() {
+        let mut g = GameState::new();
+        g.buildings.increment(BuildingType::Bakery);
+        g.prestige.points = 20;
+        let bakery = &g.building_breakdown()[0];
+        assert_eq!(bakery.building, BuildingType::Bakery);
+        assert!((bakery.total.food + 2.0).abs() < 1e-9);
+        assert!((bakery.total.gold - 0.4).abs() < 1e-9);
+    }
+
+    #[wasm_bindgen_test]
+    fn yield_breakdown_lists_sources() {
+        let mut g = GameState::new();
+        g.buildings.increment(BuildingType::Quarry);
+        g.buildings.increment(BuildingType::Farm);
+        g.prestige.points = 2;
+        let b = g.yield_breakdown(ResourceKind::Stone);
+        assert_eq!(b.buildings.len(), 1);
+        assert_eq!(b.buildings[0].building, BuildingType::Quarry);
+        let sources: Vec<_> = b.modifiers.iter().map(|m| m.source.as_str()).collect();
+        assert_eq!(sources, ["Prestige"]);
+        assert!((b.total - 1.1).abs() < 1e-9);
+        assert!((g.get_resource_rate("stone".into()) - 1.1).abs() < 1e-9);
+        assert_eq!(g.yield_breakdown(ResourceKind::Food).modifiers.len(), 1);
+    }
+
+    #[wasm_bindgen_test]
+    fn effects_expire_during_offline_progress() {
+        let mut g = GameState::new();
+        g.event_chance = 0.0;
+        g.buildings.increment(BuildingType::Farm);
+        g.effects.add(EffectType::HarvestFestival);
+        g.resources.food = 10.0;
+        assert_eq!(g.get_resource_rate("food".into()), 1.0);
+        g.tick(0.0);
+        g.tick(100.0);
+        // 60 boosted seconds followed by 40 normal ones, less what workers eat
+        assert_eq!(g.resources.food, 90.0);
+        assert!(g.active_effects().is_empty());
+        assert!(g
+            .event_log
+            .contains(&"Harvest festival has ended".to_string()));
+    }
+
+    #[wasm_bindgen_test]
+    fn spell_cooldown_recovers_with_ticks() {
+        let mut g = GameState::new();
+        g.event_chance = 0.0;
+        g.resources.mana = 25.0;
+        let r = g.cast("transmute".into());
+        assert!(r.success);
+        assert_eq!(r.gained.gold, 50.0);
+        assert_eq!(g.resources.mana, 15.0);
+        let r = g.cast("transmute".into());
+        assert!(!r.success);
+        assert_eq!(r.message, "Transmute is recharging");
+        g.tick(0.0);
+        g.tick(5.0);
+        assert!(!g.cast("transmute".into()).success);
+        g.tick(10.0);
+        assert!(g.spell_cooldowns().is_empty());
+        assert!(g.cast("transmute".into()).success);
+        assert!(!g.cast("blessing".into()).success);
+        assert_eq!(g.resources.mana, 5.0);
+    }
+
+    #[wasm_bindgen_test]
+    fn time_warp_grants_production() {
+        let mut g = GameState::new();
+        g.buildings.increment(BuildingType::Farm);
+        g.resources.mana = 60.0;
+        g.resources.food = 10.0;
+        let r = g.cast("time_warp".into());
+        assert!(r.success);
+        // Five workers eat half of what the farm grows
+        assert_eq!(r.gained.food, TIME_WARP_SECONDS / 2.0);
+        assert_eq!(r.gained.mana, 0.0);
+        assert_eq!(g.spells.cooldown(Spell::TimeWarp), 300.0);
+        g.resources.mana = 20.0;
+        assert_eq!(g.cast("ward".into()).effect, Some(EffectType::Ward));
+        assert!(g.effects.is_active(EffectType::Ward));
+    }
+
+    #[wasm_bindgen_test]
+    fn queue_runs_during_offline_progress() {
+        let mut g = GameState::new();
+        g.event_chance = 0.0;
+        assert!(!g.queue_build("farm".into(), 2));
+        g.research.unlock(Tech::Automation);
+        assert!(g.queue_build("farm".into(), 2));
+        assert!(g.queue_research("mining".into()));
+        assert!(g.queue_upgrade("efficiency".into()));
+        g.resources = res(100.0, 100.0, 0.0, 0.0, 0.0);
+        g.resources.science = 100.0;
+        let data = g.save_string();
+        let mut g = GameState::load_string(&data).unwrap();
+        g.event_chance = 0.0;
+        assert_eq!(g.automation.queue().len(), 3);
+        g.tick(0.0);
+        g.tick(3.0);
+        assert_eq!(g.buildings.level(BuildingType::Farm), 2);
+        assert!(g.research.is_unlocked(Tech::Mining));
+        assert_eq!(g.upgrades.level(UpgradeType::Efficiency), 0);
+        assert_eq!(
+            g.automation.front(),
+            Some(QueuedAction::Upgrade {
+                upgrade: UpgradeType::Efficiency
+            })
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn locked_queue_head_is_dropped() {
+        let mut g = GameState::new();
+        g.event_chance = 0.0;
+        g.research.unlock(Tech::Automation);
+        assert!(g.queue_build("mine".into(), 1));
+        assert!(g.queue_build("farm".into(), 1));
+        g.resources = res(100.0, 100.0, 10.0, 0.0, 0.0);
+        g.tick(0.0);
+        g.tick(2.0);
+        assert_eq!(g.buildings.level(BuildingType::Mine), 0);
+        assert_eq!(g.buildings.level(BuildingType::Farm), 1);
+        assert!(g.automation.queue().is_empty());
+        assert!(g
+            .event_log
+            .contains(&"Removed Mine from the queue: Requires Mining".to_string()));
+    }
+
+    #[wasm_bindgen_test]
+    fn auto_build_respects_spend_limit() {
+        let mut g = GameState::new();
+        g.event_chance = 0.0;
+        g.prestige.points = AUTOMATION_PRESTIGE;
+        assert!(g.set_auto_build("quarry".into(), 0.5));
+        g.resources = res(10.0, 30.0, 0.0, 0.0, 0.0);
+        g.tick(0.0);
+        g.tick(1.0);
+        assert_eq!(g.buildings.level(BuildingType::Quarry), 1);
+        // The next quarry needs 5.75 wood, more than half of the 5 left
+        g.tick(2.0);
+        assert_eq!(g.buildings.level(BuildingType::Quarry), 1);
+    }
+
+    #[wasm_bindgen_test]
+    fn quests_advance_and_reward() {
+        let mut g = GameState::new();
+        g.event_chance = 0.0;
+        g.resources = res(100.0, 100.0, 0.0, 0.0, 0.0);
+        assert_eq!(g.quest_status()[0].quest.title, "Build 3 farms");
+        for _ in 0..3 {
+            assert!(g.build("farm".into()).is_ok());
+        }
+        g.buildings.increment(BuildingType::LumberMill);
+        g.buildings.increment(BuildingType::LumberMill);
+        g.tick(0.0);
+        g.tick(1.0);
+        let status = &g.quest_status()[0];
+        assert_eq!(status.step, 2);
+        assert_eq!(status.progress, 0.0);
+        assert!(g
+            .event_log
+            .contains(&"Quest complete: Build 3 farms".to_string()));
+        g.buildings.increment(BuildingType::Quarry);
+        assert_eq!(g.quest_status()[0].progress, 0.5);
+        g.buildings.increment(BuildingType::Quarry);
+        g.tick(2.0);
+        assert_eq!(g.resources.science, 100.0);
+        assert_eq!(g.quest_status()[0].quest.title, "Research Mining");
+        g.resources.gold = 1_000_000.0;
+        g.prestige();
+        assert_eq!(g.quest_status()[0].quest.title, "Build 3 farms");
+    }
+
+    #[wasm_bindgen_test]
+    fn brownout_scales_powered_buildings() {
+        let mut g = GameState::new();
+        g.event_chance = 0.0;
+        g.population.count = 20;
+        g.buildings.increment(BuildingType::Generator);
+        for _ in 0..3 {
+            g.buildings.increment(BuildingType::Lab);
+        }
+        g.resources.iron = 10.0;
+        let status = g.power_status();
+        assert_eq!(status.supply, 1.0);
+        assert_eq!(status.demand, 1.5);
+        assert!((status.satisfaction - 2.0 / 3.0).abs() < 1e-9);
+        assert!((g.get_resource_rate("science".into()) - 1.0).abs() < 1e-9);
+        g.resources.energy = 5.0;
+        assert_eq!(g.power_status().satisfaction, 1.0);
+        assert_eq!(g.get_resource_rate("science".into()), 1.5);
+        // A short tick can only draw on stored energy for its own length
+        g.tick_rate = 0.2;
+        g.resources.energy = 0.05;
+        assert!((g.power_status().satisfaction - 5.0 / 6.0).abs() < 1e-9);
+    }
+
+    #[wasm_bindgen_test]
+    fn batteries_raise_energy_storage() {
+        let mut g = GameState::new();
+        g.event_chance = 0.0;
+        g.buildings.increment(BuildingType::Generator);
+        g.resources.iron = 100.0;
+        g.resources.food = 100.0;
+        g.tick(0.0);
+        g.tick(30.0);
+        assert_eq!(g.resources.energy, power::BASE_ENERGY_STORAGE);
+        g.buildings.increment(BuildingType::Battery);
+        g.tick(60.0);
+        assert_eq!(g.power_status().capacity, 60.0);
+        assert!((g.resources.energy - 40.0).abs() < 1e-9);
+        // Energy saved before storage existed is kept but cannot grow
+        g.resources.energy = 500.0;
+        g.tick(61.0);
+        assert_eq!(g.resources.energy, 500.0);
+    }
+
+    #[wasm_bindgen_test]
+    fn build_explains_refusal() {
+        let mut g = GameState::new();
+        g.resources = res(4.0, 10.0, 0.0, 0.0, 0.0);
+        assert_eq!(
+            g.build("castle".into()),
+            Err(ActionError::UnknownId {
+                id: "castle".into()
+            })
+        );
+        assert_eq!(
+            g.build("mine".into()),
+            Err(ActionError::Locked {
+                requires: Tech::Mining
+            })
+        );
+        assert_eq!(
+            g.build("farm".into()),
+            Err(ActionError::Insufficient {
+                missing: res(6.0, 0.0, 0.0, 0.0, 0.0)
+            })
+        );
+        assert_eq!(g.resources.wood, 4.0);
+    }
+
+    #[wasm_bindgen_test]
+    fn processing_chain_needs_research() {
+        let mut g = GameState::new();
+        g.event_chance = 0.0;
+        g.population.count = 20;
+        g.resources = res(500.0, 500.0, 0.0, 500.0, 500.0);
+        for name in ["smelter", "forge", "mint"] {
+            assert!(matches!(
+                g.build(name.into()),
+                Err(ActionError::Locked { .. })
+            ));
+        }
+        g.research.unlock(Tech::Metallurgy);
+        g.research.unlock(Tech::Smithing);
+        g.research.unlock(Tech::Minting);
+        for name in ["smelter", "forge", "mint"] {
+            assert!(g.build(name.into()).is_ok());
+        }
+        g.resources.energy = 1.0;
+        assert_eq!(g.get_resource_rate("tools".into()), 0.5);
+        assert!((g.get_resource_rate("coins".into()) - 0.1).abs() < 1e-9);
+        // The forge uses more iron than the smelter makes
+        assert_eq!(g.get_resource_rate("iron".into()), -1.0);
+    }
+
+    #[wasm_bindgen_test]
+    fn coins_count_towards_prestige() {
+        let mut g = GameState::new();
+        g.resources.coins = 1000.0;
+        g.prestige();
+        assert_eq!(g.prestige.points, 1);
     }
 
     #[wasm_bindgen_test]
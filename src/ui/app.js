@@ -2,8 +2,9 @@ import init, { Game } from '../../pkg/incremental_rust_game.js';
 import { wasm_base64 } from '../../pkg/wasm_base64.js';
 import { el, button, displayName } from './components.js';
 
-const resourceNames = ['wood','stone','food','iron','gold','energy','science','mana'];
-const buildingNames = ['farm','lumber_mill','quarry','mine','bakery','generator','lab','shrine'];
+let resourceNames = [];
+let buildingNames = [];
+const labels = {};
 
 const resDiv = document.getElementById('resources');
 const bldDiv = document.getElementById('buildings');
@@ -16,15 +17,29 @@ const loadBtn = document.getElementById('load');
 const resetBtn = document.getElementById('reset');
 const buildingButtons = {};
 let lastSave = 0;
+let game = null;
 let currentToast = null;
 
+function loadCatalogue(){
+    const resources=JSON.parse(Game.list_resources());
+    const buildings=JSON.parse(Game.list_buildings());
+    resourceNames=resources.map(r=>r.id);
+    buildingNames=buildings.map(b=>b.id);
+    const techs=JSON.parse(Game.list_techs());
+    [...resources,...buildings,...techs].forEach(e=>{ labels[e.id]=e.name; });
+}
+
+function label(id){
+    return labels[id] ?? displayName(id);
+}
+
 function updateLoadButton(){
     loadBtn.disabled = !localStorage.getItem('idle-save');
 }
 
 function updateAchievements(){
     achDiv.innerHTML='';
-    const list=JSON.parse(Game.achievements());
+    const list=JSON.parse(game.achievements());
     if(list.length===0) return;
     const ul=el('ul');
     list.forEach(id=>{
@@ -35,10 +50,19 @@ function updateAchievements(){
 
 function formatCost(cost){
     return resourceNames
-        .map(r=>cost[r]>0?`${cost[r].toFixed(1)} ${displayName(r)}`:'')
+        .map(r=>cost[r]>0?`${cost[r].toFixed(1)} ${label(r)}`:'')
         .filter(Boolean)
         .join(', ');
 }
+function formatWait(wait){
+    if(!wait || wait.seconds===0) return '';
+    if(wait.seconds===null) return ` – no ${label(wait.bottleneck)} income`;
+    const total=Math.ceil(wait.seconds);
+    const m=Math.floor(total/60);
+    const sec=total%60;
+    return ` – affordable in ${m>0?`${m}m `:''}${sec}s`;
+}
+
 function log(msg){
     const atBottom=logDiv.scrollTop+logDiv.clientHeight>=logDiv.scrollHeight-5;
     const p=document.createElement('p');
@@ -56,65 +80,84 @@ function toast(msg, color='green'){
 }
 
 function updateResources(){
+    const snap=JSON.parse(game.snapshot());
     resDiv.innerHTML='';
     resourceNames.forEach(r=>{
-        const val=Game.get_resource(r);
-        const rate=Game.get_resource_rate(r);
+        const val=snap.resources[r];
+        const rate=snap.rates[r];
         const color=rate>0?'text-green-400':rate<0?'text-red-400':'text-gray-400';
         const rateStr=`(${rate>=0?'+':''}${rate.toFixed(1)}/s)`;
         resDiv.appendChild(
             el('div',{class:'mb-1 mx-2'},
-                el('span',{},`${displayName(r)} ${val.toFixed(1)}`),
+                el('span',{},`${label(r)} ${val.toFixed(1)}`),
                 el('span',{class:`ml-1 ${color}`},rateStr)
             )
         );
     });
-    buildingNames.forEach(name=>{
-        const cost=JSON.parse(Game.building_cost(name));
-        const btn=buildingButtons[name];
+    const pop=snap.population;
+    resDiv.appendChild(
+        el('div',{class:'mb-1 mx-2',title:`${pop.idle} idle`},
+            el('span',{},`Workers ${pop.count}/${pop.cap}`)
+        )
+    );
+    const waits={};
+    JSON.parse(game.time_to_afford()).buildings.forEach(e=>{ waits[e.id]=e; });
+    snap.buildings.forEach(b=>{
+        const btn=buildingButtons[b.id];
         if(!btn) return;
-        const count=Game.building_count(name);
-        btn.textContent=`Build ${displayName(name)} (${count}) – ${formatCost(cost)}`;
-        btn.title=formatCost(cost);
-        const affordable=resourceNames.every(r=>Game.get_resource(r)>=cost[r]);
-        btn.disabled=!affordable;
+        btn.textContent=`Build ${b.name} (${countLabel(b)}) – ${formatCost(b.cost)}`;
+        btn.title=b.requires?`Requires ${label(b.requires)}`:`${formatCost(b.cost)}${formatWait(waits[b.id])}`;
+        btn.disabled=!b.affordable || b.requires!==null;
     });
+    snap.log.forEach(log);
+}
+
+function countLabel({count,active}){
+    return active===count?`${count}`:`${active}/${count} running`;
+}
+
+function activeToggle(name,label,delta,tooltip){
+    const b=el('button',{class:'btn-secondary m-1',title:tooltip});
+    b.textContent=label;
+    b.onclick=()=>{
+        game.set_active(name,Math.max(0,game.active_count(name)+delta));
+        updateResources();
+    };
+    return b;
 }
 
 function buildUI(){
     bldDiv.innerHTML='';
     buildingNames.forEach(name=>{
-        const cost=JSON.parse(Game.building_cost(name));
-        const btn=button(`Build ${displayName(name)} (${Game.building_count(name)}) – ${formatCost(cost)}`,
+        const btn=button(`Build ${label(name)}`,
             ()=>{
-                if(Game.build(name)){
-                    log(`Built ${displayName(name)}`);
+                const result=JSON.parse(game.build(name));
+                if(result.ok){
+                    log(`Built ${label(name)}`);
                 }else{
-                    log(`Cannot build ${displayName(name)}`);
+                    log(`Cannot build ${label(name)}: ${result.message}`);
                 }
                 updateResources();
-            },
-            formatCost(cost)
+            }
         );
         buildingButtons[name]=btn;
-        bldDiv.appendChild(btn);
+        bldDiv.appendChild(el('div',{class:'flex items-center'},
+            btn,
+            activeToggle(name,'−',-1,'Pause one'),
+            activeToggle(name,'+',1,'Resume one')
+        ));
     });
 }
 
 function tick(){
-    Game.tick(Date.now()/1000);
+    game.tick(Date.now()/1000);
     updateResources();
-    let msg=Game.pop_log();
-    while(msg){
-        log(msg);
-        msg=Game.pop_log();
-    }
     updateAchievements();
 }
 
 function saveGame(showToast=true){
     lastSave=Date.now();
-    localStorage.setItem('idle-save',Game.save());
+    localStorage.setItem('idle-save',game.save());
     saveStamp.textContent=new Date(lastSave).toLocaleTimeString();
     updateLoadButton();
     if(showToast) toast('Saved');
@@ -123,7 +166,7 @@ function saveGame(showToast=true){
 function loadGame(){
     const d=localStorage.getItem('idle-save');
     if(d){
-        Game.load(d);
+        game.load(d);
         updateResources();
         toast('Loaded');
         updateAchievements();
@@ -152,7 +195,8 @@ async function run(){
         return;
     }
 
-    new Game();
+    game=new Game();
+    loadCatalogue();
     buildUI();
     updateResources();
     setInterval(tick,1000);
@@ -162,7 +206,7 @@ async function run(){
     loadBtn.onclick=loadGame;
     resetBtn.onclick=()=>{ localStorage.clear(); location.reload(); };
     tickInput.oninput=()=>{
-        Game.set_tick_rate(parseFloat(tickInput.value));
+        game.set_tick_rate(parseFloat(tickInput.value));
         localStorage.setItem('tick-rate', tickInput.value);
     };
 
@@ -177,11 +221,11 @@ async function run(){
     const savedRate=localStorage.getItem('tick-rate');
     if(savedRate){
         tickInput.value=savedRate;
-        Game.set_tick_rate(parseFloat(savedRate));
+        game.set_tick_rate(parseFloat(savedRate));
     }
 
     const d=localStorage.getItem('idle-save');
-    if(d){ Game.load(d); }
+    if(d){ game.load(d); }
     updateLoadButton();
     updateAchievements();
     saveGame(false);
@@ -1,22 +1,106 @@
-use crate::resources::{res, Resources};
+use crate::buildings::BuildingType;
+use crate::catalogue::{self, Catalogue, UnknownId};
+use crate::error::ActionError;
+use crate::modifiers::{Modifier, ModifierOp};
+use crate::resources::{res, ResourceKind, Resources};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
 
 /// Upgrades obtainable in game
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
 pub enum UpgradeType {
     /// Increases all yields
+    #[serde(alias = "Efficiency")]
     Efficiency,
     /// Increases resource storage cap
+    #[serde(alias = "Storage")]
     Storage,
     /// Boost mana production
+    #[serde(alias = "AlchemyBoost")]
     AlchemyBoost,
+    /// Doubles farm output
+    #[serde(alias = "IronPlough")]
+    IronPlough,
+    /// Doubles lumber mill output
+    #[serde(alias = "SteamSaw")]
+    SteamSaw,
+    /// Doubles quarry output
+    #[serde(alias = "BlastingPowder")]
+    BlastingPowder,
+}
+
+impl UpgradeType {
+    /// Every upgrade in display order
+    pub const ALL: [UpgradeType; 6] = [
+        UpgradeType::Efficiency,
+        UpgradeType::Storage,
+        UpgradeType::AlchemyBoost,
+        UpgradeType::IronPlough,
+        UpgradeType::SteamSaw,
+        UpgradeType::BlastingPowder,
+    ];
+}
+
+impl Catalogue for UpgradeType {
+    const ENTRIES: &'static [Self] = &Self::ALL;
+
+    fn id(self) -> &'static str {
+        match self {
+            UpgradeType::Efficiency => "efficiency",
+            UpgradeType::Storage => "storage",
+            UpgradeType::AlchemyBoost => "alchemy_boost",
+            UpgradeType::IronPlough => "iron_plough",
+            UpgradeType::SteamSaw => "steam_saw",
+            UpgradeType::BlastingPowder => "blasting_powder",
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            UpgradeType::Efficiency => "Efficiency",
+            UpgradeType::Storage => "Storage",
+            UpgradeType::AlchemyBoost => "Alchemy Boost",
+            UpgradeType::IronPlough => "Iron Plough",
+            UpgradeType::SteamSaw => "Steam Saw",
+            UpgradeType::BlastingPowder => "Blasting Powder",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            UpgradeType::Efficiency => "Increases all yields",
+            UpgradeType::Storage => "Increases resource storage",
+            UpgradeType::AlchemyBoost => "Increases mana production",
+            UpgradeType::IronPlough => "Doubles farm output",
+            UpgradeType::SteamSaw => "Doubles lumber mill output",
+            UpgradeType::BlastingPowder => "Doubles quarry output",
+        }
+    }
+}
+
+impl FromStr for UpgradeType {
+    type Err = UnknownId;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        catalogue::parse(s)
+    }
+}
+
+impl fmt::Display for UpgradeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
 }
 
 struct UpgradeInfo {
     base_cost: Resources,
     growth: f64,
     multiplier: f64,
+    target: Option<BuildingType>,
+    resource: Option<ResourceKind>,
 }
 
 fn info(ty: UpgradeType) -> UpgradeInfo {
@@ -25,22 +109,49 @@ fn info(ty: UpgradeType) -> UpgradeInfo {
             base_cost: res(50.0, 50.0, 0.0, 0.0, 0.0),
             growth: 1.5,
             multiplier: 1.1,
+            target: None,
+            resource: None,
         },
         UpgradeType::Storage => UpgradeInfo {
             base_cost: res(100.0, 100.0, 0.0, 0.0, 0.0),
             growth: 1.7,
             multiplier: 1.2,
+            target: None,
+            resource: None,
         },
         UpgradeType::AlchemyBoost => UpgradeInfo {
             base_cost: res(0.0, 0.0, 0.0, 0.0, 200.0),
             growth: 2.0,
             multiplier: 1.5,
+            target: None,
+            resource: Some(ResourceKind::Mana),
+        },
+        UpgradeType::IronPlough => UpgradeInfo {
+            base_cost: res(100.0, 50.0, 0.0, 20.0, 0.0),
+            growth: 3.0,
+            multiplier: 2.0,
+            target: Some(BuildingType::Farm),
+            resource: None,
+        },
+        UpgradeType::SteamSaw => UpgradeInfo {
+            base_cost: res(50.0, 100.0, 0.0, 20.0, 0.0),
+            growth: 3.0,
+            multiplier: 2.0,
+            target: Some(BuildingType::LumberMill),
+            resource: None,
+        },
+        UpgradeType::BlastingPowder => UpgradeInfo {
+            base_cost: res(100.0, 50.0, 0.0, 20.0, 10.0),
+            growth: 3.0,
+            multiplier: 2.0,
+            target: Some(BuildingType::Quarry),
+            resource: None,
         },
     }
 }
 
 /// Owned upgrades
-#[derive(Default, Serialize, Deserialize)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct Upgrades {
     levels: HashMap<UpgradeType, u32>,
 }
@@ -56,6 +167,27 @@ impl Upgrades {
         info(ty).multiplier.powi(self.level(ty) as i32)
     }
 
+    /// Output modifiers from purchased upgrades
+    pub fn modifiers(&self) -> Vec<Modifier> {
+        let mut mods: Vec<_> = self
+            .levels
+            .keys()
+            // Storage raises capacity rather than output
+            .filter(|&&ty| ty != UpgradeType::Storage)
+            .map(|&ty| {
+                let i = info(ty);
+                Modifier {
+                    source: format!("{} upgrade", ty.name()),
+                    building: i.target,
+                    resource: i.resource,
+                    op: ModifierOp::Multiplicative(self.multiplier(ty)),
+                }
+            })
+            .collect();
+        mods.sort_by(|a, b| a.source.cmp(&b.source));
+        mods
+    }
+
     /// Cost to purchase next level
     pub fn cost(&self, ty: UpgradeType) -> Resources {
         let i = info(ty);
@@ -63,14 +195,15 @@ impl Upgrades {
         i.base_cost.scale(factor)
     }
 
-    /// Attempt to purchase
-    pub fn purchase(&mut self, ty: UpgradeType, res: &mut Resources) -> bool {
+    /// Attempt to purchase the next level
+    pub fn purchase(&mut self, ty: UpgradeType, res: &mut Resources) -> Result<(), ActionError> {
         let cost = self.cost(ty);
-        if res.subtract(&cost) {
-            *self.levels.entry(ty).or_insert(0) += 1;
-            true
-        } else {
-            false
+        if !res.subtract(&cost) {
+            return Err(ActionError::Insufficient {
+                missing: res.shortfall(&cost),
+            });
         }
+        *self.levels.entry(ty).or_insert(0) += 1;
+        Ok(())
     }
 }
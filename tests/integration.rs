@@ -1,4 +1,4 @@
-use incremental_rust_game::{farm_loss_event, res, BuildingType, GameState};
+use incremental_rust_game::{farm_loss_event, res, BuildingType, Game, GameState, SharedGame};
 use rand::{rngs::StdRng, SeedableRng};
 use wasm_bindgen_test::wasm_bindgen_test;
 
@@ -6,12 +6,13 @@ use wasm_bindgen_test::wasm_bindgen_test;
 fn ten_minutes_growth() {
     let mut g = GameState::new();
     g.event_chance = 0.0;
-    g.resources = res(20.0, 20.0, 0.0, 0.0, 0.0);
-    assert!(g.build("farm".into()));
+    g.resources = res(20.0, 20.0, 10.0, 0.0, 0.0);
+    assert!(g.build("farm".into()).is_ok());
     let start = g.resources.food;
     g.tick(0.0); // initialize timestamp
     g.tick(600.0);
-    assert_eq!(g.resources.food, start + 600.0);
+    // Five workers eat half of the harvest
+    assert_eq!(g.resources.food, start + 300.0);
 }
 
 #[wasm_bindgen_test]
@@ -46,3 +47,35 @@ fn prestige_resets() {
     assert!(g.prestige.points > 0);
     assert_eq!(g.buildings.level(BuildingType::Farm), 0);
 }
+
+#[wasm_bindgen_test]
+fn games_are_independent() {
+    let mut a = Game::new();
+    let b = Game::new();
+    assert!(a.build("farm").contains("\"ok\":true"));
+    assert_eq!(a.building_count("farm"), 1);
+    assert_eq!(b.building_count("farm"), 0);
+    let mut copy = Game::new();
+    copy.load(&a.save());
+    assert_eq!(copy.building_count("farm"), 1);
+    assert_eq!(SharedGame::building_count("farm"), 0);
+}
+
+#[wasm_bindgen_test]
+fn shared_game_mirrors_game() {
+    let mut a = Game::new();
+    assert!(a.build("farm").contains("\"ok\":true"));
+    SharedGame::load(&a.save());
+    assert_eq!(SharedGame::building_count("farm"), 1);
+    assert_eq!(SharedGame::sell("farm", 1), 1);
+    assert_eq!(SharedGame::building_count("farm"), 0);
+    assert_eq!(a.building_count("farm"), 1);
+}
+
+#[cfg(feature = "debug-tools")]
+#[wasm_bindgen_test]
+fn shared_game_mirrors_debug_tools() {
+    assert!(SharedGame::grant("gold", 5.0));
+    assert_eq!(SharedGame::get_resource("gold"), 5.0);
+    assert!(!SharedGame::grant("gold", -1.0));
+}